@@ -2,6 +2,10 @@ use copy_to_output::copy_to_output;
 use std::env;
 
 fn main() {
+    // The assets are only used by the window.
+    if env::var_os("CARGO_FEATURE_GUI").is_none() {
+        return;
+    }
     // Copy directory "assets" to target directory, rerun if any file change in this directory.
     println!("cargo:rerun-if-changed=assets/*");
     copy_to_output("assets", &env::var("PROFILE").unwrap())
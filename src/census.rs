@@ -0,0 +1,312 @@
+use crate::pool::{Pool, Topology};
+use std::collections::{HashSet, VecDeque};
+
+/// Longest period looked for when identifying an isolated object.
+const MAX_OBJECT_PERIOD: u32 = 30;
+
+/// Name given to objects which are not periodic within `MAX_OBJECT_PERIOD` generations.
+pub const UNKNOWN_OBJECT: &str = "zz_UNKNOWN";
+
+/// Characters used by the extended Wechsler format, the index being the encoded value.
+const WECHSLER_CHARS: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+/// A connected group of alive cells, as (wrapped, unwrapped) coordinates.
+/// The unwrapped coordinates keep objects crossing the edges of a torus in one piece.
+type Component = Vec<((u32, u32), (i64, i64))>;
+
+/// Separates a stabilized pool into objects and returns the apgcode of each of them,
+/// e.g. `xs4_33` for a block or `xq4_153` for a glider.
+///
+/// Spaceships are identified and removed first, then the remaining cells are grouped
+/// over a whole period so that every phase of an oscillator stays in the same object.
+pub fn census(pool: &Pool) -> Vec<String> {
+    let mut pool = pool.clone();
+    let mut objects = Vec::new();
+
+    // Remove spaceships, their path would otherwise merge them with everything else.
+    for component in components(&pool) {
+        let cells: Vec<(i64, i64)> = component.iter().map(|(_, cell)| *cell).collect();
+        let code = apgcode(&cells);
+        if code.starts_with("xq") {
+            for ((row, column), _) in component {
+                pool.set_cell(row, column, false);
+            }
+            objects.push(code);
+        }
+    }
+
+    // Union of all the phases of the remaining oscillators.
+    let mut union = pool.clone();
+    let mut phase = pool.clone();
+    for _ in 1..MAX_OBJECT_PERIOD {
+        phase.step();
+        if phase == pool {
+            break;
+        }
+        union += phase.clone();
+    }
+
+    for component in components(&union) {
+        let cells: Vec<(i64, i64)> = component
+            .iter()
+            .filter(|((row, column), _)| pool.get_cell(*row, *column))
+            .map(|(_, cell)| *cell)
+            .collect();
+        if !cells.is_empty() {
+            objects.push(apgcode(&cells));
+        }
+    }
+    objects
+}
+
+/// Returns the groups of 8-connected alive cells of the pool.
+fn components(pool: &Pool) -> Vec<Component> {
+    let height = pool.height() as i64;
+    let width = pool.width() as i64;
+    let mut visited = HashSet::new();
+    let mut result = Vec::new();
+
+    for row in 0..pool.height() {
+        for column in 0..pool.width() {
+            if !pool.get_cell(row, column) || !visited.insert((row, column)) {
+                continue;
+            }
+            let mut component = Vec::new();
+            let mut queue = VecDeque::from([((row, column), (row as i64, column as i64))]);
+            while let Some((wrapped, unwrapped)) = queue.pop_front() {
+                component.push((wrapped, unwrapped));
+                for (i, j) in NEIGHBORHOOD {
+                    let neighbor = (unwrapped.0 + i, unwrapped.1 + j);
+                    let wrapped_neighbor = match pool.topology() {
+                        Topology::Torus => (
+                            neighbor.0.rem_euclid(height) as u32,
+                            neighbor.1.rem_euclid(width) as u32,
+                        ),
+                        Topology::Bounded => {
+                            if neighbor.0 < 0
+                                || neighbor.0 >= height
+                                || neighbor.1 < 0
+                                || neighbor.1 >= width
+                            {
+                                continue;
+                            }
+                            (neighbor.0 as u32, neighbor.1 as u32)
+                        }
+                    };
+                    if pool.get_cell(wrapped_neighbor.0, wrapped_neighbor.1)
+                        && visited.insert(wrapped_neighbor)
+                    {
+                        queue.push_back((wrapped_neighbor, neighbor));
+                    }
+                }
+            }
+            result.push(component);
+        }
+    }
+    result
+}
+
+/// Relative positions of the 8 neighbors of a cell.
+const NEIGHBORHOOD: [(i64, i64); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Evolves an isolated object until it comes back to its initial shape,
+/// and names it after its period, displacement and canonical phase.
+pub fn apgcode(cells: &[(i64, i64)]) -> String {
+    // Any spaceship moves by less than half its period, leave enough room around the object.
+    const MARGIN: i64 = MAX_OBJECT_PERIOD as i64 / 2 + 2;
+
+    let initial = normalized(cells);
+    let height = initial.iter().map(|(row, _)| *row).max().unwrap_or(0) + 1;
+    let width = initial.iter().map(|(_, column)| *column).max().unwrap_or(0) + 1;
+    let mut pool = Pool::new((width + 2 * MARGIN) as u32, (height + 2 * MARGIN) as u32);
+    for (row, column) in &initial {
+        pool.set_cell((row + MARGIN) as u32, (column + MARGIN) as u32, true);
+    }
+
+    let mut phases = vec![initial.clone()];
+    for generation in 1..=MAX_OBJECT_PERIOD {
+        pool.step();
+        let alive = alive_cells(&pool);
+        if alive.is_empty() {
+            break;
+        }
+        let phase = normalized(&alive);
+        if phase == initial {
+            let min_row = alive.iter().map(|(row, _)| *row).min();
+            let min_column = alive.iter().map(|(_, column)| *column).min();
+            let moved = (min_row, min_column) != (Some(MARGIN), Some(MARGIN));
+            let prefix = if moved {
+                format!("xq{}", generation)
+            } else if generation == 1 {
+                format!("xs{}", initial.len())
+            } else {
+                format!("xp{}", generation)
+            };
+            let representation = phases
+                .iter()
+                .flat_map(|phase| orientations(phase))
+                .map(|orientation| wechsler(&orientation))
+                .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+                .unwrap();
+            return format!("{}_{}", prefix, representation);
+        }
+        phases.push(phase);
+    }
+    UNKNOWN_OBJECT.to_owned()
+}
+
+fn alive_cells(pool: &Pool) -> Vec<(i64, i64)> {
+    let mut cells = Vec::new();
+    for row in 0..pool.height() {
+        for column in 0..pool.width() {
+            if pool.get_cell(row, column) {
+                cells.push((row as i64, column as i64));
+            }
+        }
+    }
+    cells
+}
+
+/// Translates the cells so that the smallest row and column are zero, and sorts them.
+fn normalized(cells: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let min_row = cells.iter().map(|(row, _)| *row).min().unwrap_or(0);
+    let min_column = cells.iter().map(|(_, column)| *column).min().unwrap_or(0);
+    let mut result: Vec<(i64, i64)> = cells
+        .iter()
+        .map(|(row, column)| (row - min_row, column - min_column))
+        .collect();
+    result.sort();
+    result
+}
+
+/// Returns the 8 rotations and reflections of the cells.
+fn orientations(cells: &[(i64, i64)]) -> Vec<Vec<(i64, i64)>> {
+    (0..8)
+        .map(|orientation| {
+            let transformed: Vec<(i64, i64)> = cells
+                .iter()
+                .map(|&(row, column)| {
+                    // Bit 2 swaps the axes, bits 1 and 0 mirror rows and columns.
+                    let (row, column) = if orientation & 4 != 0 {
+                        (column, row)
+                    } else {
+                        (row, column)
+                    };
+                    let row = if orientation & 2 != 0 { -row } else { row };
+                    let column = if orientation & 1 != 0 {
+                        -column
+                    } else {
+                        column
+                    };
+                    (row, column)
+                })
+                .collect();
+            normalized(&transformed)
+        })
+        .collect()
+}
+
+/// Encodes normalized cells in the extended Wechsler format :
+/// strips of 5 rows, each column of a strip being one character, strips separated by `z`.
+fn wechsler(cells: &[(i64, i64)]) -> String {
+    let height = cells.iter().map(|(row, _)| *row).max().unwrap_or(0) + 1;
+    let width = cells.iter().map(|(_, column)| *column).max().unwrap_or(0) + 1;
+    let cells: HashSet<&(i64, i64)> = cells.iter().collect();
+
+    let mut strips = Vec::new();
+    for strip_start in (0..height).step_by(5) {
+        let mut strip = String::new();
+        for column in 0..width {
+            let mut value = 0;
+            for bit in 0..5 {
+                if cells.contains(&(strip_start + bit, column)) {
+                    value |= 1 << bit;
+                }
+            }
+            strip.push(WECHSLER_CHARS[value] as char);
+        }
+        strips.push(compress_zeros(strip.trim_end_matches('0')));
+    }
+    strips.join("z")
+}
+
+/// Replaces runs of zeros with the shorthands `w` (2), `x` (3) and `y` (4 to 39).
+fn compress_zeros(strip: &str) -> String {
+    let mut result = String::new();
+    let mut zeros = 0;
+    for character in strip.chars().chain(std::iter::once('\n')) {
+        if character == '0' {
+            zeros += 1;
+            continue;
+        }
+        while zeros > 0 {
+            let run = zeros.min(39);
+            match run {
+                1 => result.push('0'),
+                2 => result.push('w'),
+                3 => result.push('x'),
+                _ => {
+                    result.push('y');
+                    result.push(WECHSLER_CHARS[run - 4] as char);
+                }
+            }
+            zeros -= run;
+        }
+        if character != '\n' {
+            result.push(character);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apgcode_known_objects() {
+        let block = [(0, 0), (0, 1), (1, 0), (1, 1)];
+        assert_eq!(apgcode(&block), "xs4_33");
+        let beehive = [(0, 1), (0, 2), (1, 0), (1, 3), (2, 1), (2, 2)];
+        assert_eq!(apgcode(&beehive), "xs6_696");
+        let blinker = [(5, 5), (5, 6), (5, 7)];
+        assert_eq!(apgcode(&blinker), "xp2_7");
+        let glider = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+        assert_eq!(apgcode(&glider), "xq4_153");
+    }
+
+    #[test]
+    fn compress_zeros_runs() {
+        assert_eq!(compress_zeros("1001"), "1w1");
+        assert_eq!(compress_zeros("100001"), "1y01");
+        assert_eq!(compress_zeros("1000"), "1x");
+    }
+
+    #[test]
+    fn census_separates_objects() {
+        let mut pool = Pool::new(12, 12);
+        pool.set_topology(Topology::Torus);
+        // Block crossing the left and right edges.
+        pool.set_cell(1, 11, true);
+        pool.set_cell(1, 0, true);
+        pool.set_cell(2, 11, true);
+        pool.set_cell(2, 0, true);
+        // Blinker
+        pool.set_cell(7, 5, true);
+        pool.set_cell(7, 6, true);
+        pool.set_cell(7, 7, true);
+
+        let mut objects = census(&pool);
+        objects.sort();
+        assert_eq!(objects, vec!["xp2_7", "xs4_33"]);
+    }
+}
@@ -0,0 +1,622 @@
+#[cfg(feature = "tui")]
+use crate::tui::Glyphs;
+use clap::error::ErrorKind;
+use clap::{value_parser, CommandFactory, Parser, Subcommand};
+#[cfg(feature = "gui")]
+use game_of_life::elementary::Elementary;
+use game_of_life::elementary::ElementaryRule;
+#[cfg(feature = "gui")]
+use game_of_life::generations::GenerationsPool;
+use game_of_life::generations::GenerationsRule;
+#[cfg(feature = "gui")]
+use game_of_life::grid::Grid as _;
+#[cfg(feature = "gui")]
+use game_of_life::larger_than_life::LtlPool;
+use game_of_life::larger_than_life::LtlRule;
+#[cfg(feature = "gui")]
+use game_of_life::lenia::LeniaPool;
+use game_of_life::lenia::LeniaRule;
+#[cfg(feature = "gui")]
+use game_of_life::margolus::MargolusPool;
+use game_of_life::margolus::MargolusRule;
+use game_of_life::pattern::{self, Format};
+use game_of_life::pool::{Pool, RandomFill, Topology};
+use game_of_life::rule::Rule;
+#[cfg(feature = "gui")]
+use game_of_life::rule_file::{self, RuleFilePool};
+#[cfg(feature = "gui")]
+use game_of_life::wireworld::{self, WireWorld};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// John Conway's game of life.
+///
+/// Without a command, opens a window showing the board.
+/// The board options are given before the command.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Args {
+    /// Width of the board in cells [default: 128, or the width of the pattern if larger]
+    #[arg(long, value_parser = value_parser!(u32).range(1..))]
+    pub width: Option<u32>,
+
+    /// Height of the board in cells [default: 72, or the height of the pattern if larger]
+    #[arg(long, value_parser = value_parser!(u32).range(1..))]
+    pub height: Option<u32>,
+
+    /// Rule in the B3/S23 notation, B3/S2-i34q for non-totalistic rules, or B2/S34H and B1/S012V for the hexagonal and
+    /// von Neumann neighborhoods. Generations rules such as /2/3, Larger than Life rules
+    /// such as R5,C0,M1,S34..58,B34..45,NM, elementary rules such as W30, Margolus block rules such as
+    /// M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15, Lenia rules such as R=13;T=10;m=0.15;s=0.015
+    /// and WireWorld are shown in a window
+    /// [default: B3/S23, or the rule of the pattern file]
+    #[arg(long)]
+    pub rule: Option<RuleArg>,
+
+    /// Golly .rule file, with a @TABLE or @TREE section, shown in a window instead of --rule
+    #[arg(long)]
+    pub rule_file: Option<PathBuf>,
+
+    /// Behavior of the board edges, bounded or torus
+    #[arg(long, default_value = "bounded")]
+    pub topology: Topology,
+
+    /// Initial pattern, an RLE file or a plaintext .cells file, centered on the board
+    #[arg(long)]
+    pub pattern: Option<PathBuf>,
+
+    /// Seed of the random initial board, random if not given. Ignored with a pattern
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Probability for each cell of a random board to be alive, between 0 and 1
+    #[arg(long, default_value_t = 0.5, value_parser = parse_density)]
+    pub density: f64,
+
+    /// Starting speed, in percent of the maximum speed
+    #[arg(long, default_value_t = 10, value_parser = value_parser!(u8).range(1..=100))]
+    pub speed: u8,
+
+    /// Start with the simulation paused
+    #[arg(long)]
+    pub paused: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Search random 16x16 soups for objects, in the style of apgsearch, without opening a window
+    SoupSearch {
+        /// Seed of the first soup, soup i uses seed + i
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Number of soups to run
+        #[arg(long, default_value_t = 1000)]
+        soups: u64,
+        /// File in which the census is written
+        #[arg(long, default_value = "census.txt")]
+        output: PathBuf,
+    },
+    /// Plays in the terminal instead of a window, with the same keys.
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Characters drawing the cells, half-block for 2 cells per character or braille for 8
+        #[arg(long, default_value = "half-block")]
+        glyphs: Glyphs,
+    },
+    /// Runs the board for a number of generations without opening a window and writes the final state.
+    ///
+    /// Exits with 0 on success, 1 if an output file cannot be written
+    /// and 2 if the arguments or the pattern are invalid.
+    Run {
+        /// Number of generations to run
+        #[arg(long)]
+        generations: u64,
+        /// File in which the final state is written, standard output if not given
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Format of the final state, rle or plaintext [default: from the output file extension, or rle]
+        #[arg(long)]
+        format: Option<Format>,
+        /// CSV file in which the population, births, deaths and bounding box of every generation are written
+        #[arg(long)]
+        stats: Option<PathBuf>,
+    },
+}
+
+/// Rule given on the command line, whose family decides the engine.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RuleArg {
+    Life(Rule),
+    Generations(GenerationsRule),
+    LargerThanLife(LtlRule),
+    WireWorld,
+    Elementary(ElementaryRule),
+    Margolus(MargolusRule),
+    Lenia(LeniaRule),
+}
+
+impl FromStr for RuleArg {
+    type Err = String;
+
+    /// Generations rules with two states are plain life-like rules.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("wireworld") {
+            return Ok(Self::WireWorld);
+        }
+        if let Ok(rule) = s.parse() {
+            return Ok(Self::Life(rule));
+        }
+        if let Ok(rule) = s.parse() {
+            return Ok(Self::LargerThanLife(rule));
+        }
+        if let Ok(rule) = s.parse() {
+            return Ok(Self::Elementary(rule));
+        }
+        if let Ok(rule) = s.parse() {
+            return Ok(Self::Margolus(rule));
+        }
+        if let Ok(rule) = s.parse() {
+            return Ok(Self::Lenia(rule));
+        }
+        match s.parse::<GenerationsRule>() {
+            Ok(rule) if rule.states() == 2 => Ok(Self::Life(rule.rule())),
+            Ok(rule) => Ok(Self::Generations(rule)),
+            Err(_) => Err(format!(
+                "invalid rule \"{}\", expected e.g. B3/S23, 23/3, /2/3, R5,C0,M1,S34..58,B34..45,NM, W30, M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15, R=13;T=10;m=0.15;s=0.015 or WireWorld",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RuleArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleArg::Life(rule) => write!(f, "{}", rule),
+            RuleArg::Generations(rule) => write!(f, "{}", rule),
+            RuleArg::LargerThanLife(rule) => write!(f, "{}", rule),
+            RuleArg::WireWorld => write!(f, "WireWorld"),
+            RuleArg::Elementary(rule) => write!(f, "{}", rule),
+            RuleArg::Margolus(rule) => write!(f, "{}", rule),
+            RuleArg::Lenia(rule) => write!(f, "{}", rule),
+        }
+    }
+}
+
+fn parse_density(s: &str) -> Result<f64, String> {
+    let density: f64 = s
+        .parse()
+        .map_err(|_| format!("\"{}\" is not a number", s))?;
+    if (0.0..=1.0).contains(&density) {
+        Ok(density)
+    } else {
+        Err(format!("{} is not between 0 and 1", density))
+    }
+}
+
+impl Args {
+    const DEFAULT_WIDTH: u32 = 128;
+    const DEFAULT_HEIGHT: u32 = 72;
+
+    /// Builds the initial board, from the pattern file or randomly,
+    /// and the random fill used to create it or to randomize it later.
+    /// Exits with an error message if the pattern cannot be loaded or does not fit.
+    pub fn initial_pool(&self) -> (Pool, RandomFill) {
+        let random_fill = RandomFill::new(self.seed.unwrap_or_else(rand::random), self.density);
+
+        let pattern = self.pattern.as_ref().map(|path| {
+            pattern::load::<Pool, _>(path).unwrap_or_else(|error| {
+                Self::command()
+                    .error(
+                        ErrorKind::Io,
+                        format!("cannot load pattern {} : {}", path.display(), error),
+                    )
+                    .exit()
+            })
+        });
+        let (pattern_width, pattern_height) = pattern
+            .as_ref()
+            .map_or((0, 0), |pattern| (pattern.width(), pattern.height()));
+        let width = self.width.unwrap_or(Self::DEFAULT_WIDTH.max(pattern_width));
+        let height = self
+            .height
+            .unwrap_or(Self::DEFAULT_HEIGHT.max(pattern_height));
+        if pattern_width > width || pattern_height > height {
+            Self::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!(
+                        "the {}x{} pattern does not fit on a {}x{} board",
+                        pattern_width, pattern_height, width, height
+                    ),
+                )
+                .exit()
+        }
+
+        let mut pool = Pool::new(width, height);
+        match &pattern {
+            Some(pattern) => {
+                pool +=
+                    pattern.with_offset((height - pattern_height) / 2, (width - pattern_width) / 2);
+                pool.set_rule(pattern.rule());
+            }
+            None => pool.randomize_with(&random_fill),
+        }
+        if let Some(RuleArg::Life(rule)) = self.rule {
+            pool.set_rule(rule);
+        }
+        pool.set_topology(self.topology);
+        (pool, random_fill)
+    }
+
+    /// Builds the initial board of a Generations rule, `None` for life-like rules.
+    /// Alive cells of the pattern or of the random board are alive.
+    #[cfg(feature = "gui")]
+    pub fn initial_generations_pool(&self) -> Option<GenerationsPool> {
+        let Some(RuleArg::Generations(rule)) = self.rule else {
+            return None;
+        };
+        let (pool, _) = self.initial_pool();
+        Some(GenerationsPool::from_pool(&pool, rule))
+    }
+
+    /// Builds the initial board of a Larger than Life rule, `None` for other rules.
+    /// Alive cells of the pattern or of the random board are alive.
+    #[cfg(feature = "gui")]
+    pub fn initial_ltl_pool(&self) -> Option<LtlPool> {
+        let Some(RuleArg::LargerThanLife(rule)) = self.rule else {
+            return None;
+        };
+        let (pool, _) = self.initial_pool();
+        Some(LtlPool::from_pool(&pool, rule))
+    }
+
+    /// Builds the initial WireWorld circuit, `None` for other rules.
+    /// It is empty, or the alive cells of the pattern are conductors.
+    #[cfg(feature = "gui")]
+    pub fn initial_wireworld(&self) -> Option<WireWorld> {
+        if self.rule != Some(RuleArg::WireWorld) {
+            return None;
+        }
+        if self.pattern.is_none() {
+            let mut circuit = WireWorld::new(
+                self.width.unwrap_or(Self::DEFAULT_WIDTH),
+                self.height.unwrap_or(Self::DEFAULT_HEIGHT),
+            );
+            circuit.set_topology(self.topology);
+            return Some(circuit);
+        }
+        let (pool, _) = self.initial_pool();
+        let mut circuit = WireWorld::new(pool.width(), pool.height());
+        circuit.set_topology(pool.topology());
+        for (row, column) in pool.alive_cells() {
+            circuit.set_cell(row, column, wireworld::Cell::Conductor);
+        }
+        Some(circuit)
+    }
+
+    /// Builds the initial row of an elementary rule, `None` for other rules. The height is the number
+    /// of generations shown. The row is random with a seed, otherwise only its center cell is alive.
+    #[cfg(feature = "gui")]
+    pub fn initial_elementary(&self) -> Option<Elementary> {
+        let Some(RuleArg::Elementary(rule)) = self.rule else {
+            return None;
+        };
+        let width = self.width.unwrap_or(Self::DEFAULT_WIDTH);
+        let mut automaton =
+            Elementary::new(width, self.height.unwrap_or(Self::DEFAULT_HEIGHT), rule);
+        automaton.set_topology(self.topology);
+        match self.seed {
+            Some(seed) => automaton.randomize_with(&RandomFill::new(seed, self.density)),
+            None => automaton.set_cell(width / 2, true),
+        }
+        Some(automaton)
+    }
+
+    /// Builds the initial board of a Margolus block rule, `None` for other rules.
+    /// Alive cells of the pattern or of the random board are alive.
+    #[cfg(feature = "gui")]
+    pub fn initial_margolus_pool(&self) -> Option<MargolusPool> {
+        let Some(RuleArg::Margolus(rule)) = self.rule else {
+            return None;
+        };
+        let (pool, _) = self.initial_pool();
+        Some(MargolusPool::from_pool(&pool, rule))
+    }
+
+    /// Builds the initial board of a Lenia rule, `None` for other rules. Alive cells of the pattern
+    /// have the value 1, otherwise the cells filled by the random fill have random values.
+    #[cfg(feature = "gui")]
+    pub fn initial_lenia_pool(&self) -> Option<LeniaPool> {
+        let Some(RuleArg::Lenia(rule)) = self.rule else {
+            return None;
+        };
+        let (pool, random_fill) = self.initial_pool();
+        if self.pattern.is_some() {
+            return Some(LeniaPool::from_pool(&pool, rule));
+        }
+        let mut lenia = LeniaPool::new(pool.width(), pool.height(), rule);
+        lenia.set_topology(pool.topology());
+        lenia.randomize_with(&random_fill);
+        Some(lenia)
+    }
+
+    /// Builds the initial board of a rule file, `None` without one.
+    /// Alive cells of the pattern or of the random board are in state 1.
+    /// Exits with an error message if the rule file cannot be loaded.
+    #[cfg(feature = "gui")]
+    pub fn initial_rule_file_pool(&self) -> Option<RuleFilePool> {
+        let path = self.rule_file.as_ref()?;
+        let rule = rule_file::load(path).unwrap_or_else(|error| {
+            Self::command()
+                .error(
+                    ErrorKind::Io,
+                    format!("cannot load rule file {} : {}", path.display(), error),
+                )
+                .exit()
+        });
+        let (pool, _) = self.initial_pool();
+        Some(RuleFilePool::from_pool(&pool, rule))
+    }
+
+    /// Exits with an error message if the rule cannot be run by the given command.
+    pub fn require_life_rule(&self, command: &str) {
+        if let Some(path) = &self.rule_file {
+            Self::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "{} only supports life-like rules, not {}",
+                        command,
+                        path.display()
+                    ),
+                )
+                .exit()
+        }
+        let Some(rule) = self.rule.filter(|rule| !matches!(rule, RuleArg::Life(_))) else {
+            return;
+        };
+        Self::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!("{} only supports life-like rules, not {}", command, rule),
+            )
+            .exit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_arguments() {
+        let args = Args::try_parse_from([
+            "game_of_life",
+            "--width",
+            "512",
+            "--rule",
+            "B36/S23",
+            "--topology",
+            "torus",
+            "--seed",
+            "4",
+            "--density",
+            "0.25",
+            "--paused",
+        ])
+        .unwrap();
+        let (pool, random_fill) = args.initial_pool();
+        assert_eq!((pool.width(), pool.height()), (512, 72));
+        assert_eq!(pool.rule(), "B36/S23".parse().unwrap());
+        assert_eq!(pool.topology(), Topology::Torus);
+        assert_eq!(random_fill, RandomFill::new(4, 0.25));
+        assert!(args.paused);
+        assert!(args.command.is_none());
+    }
+
+    #[test]
+    fn generations_rule() {
+        let args =
+            Args::try_parse_from(["game_of_life", "--rule", "/2/3", "--width", "10"]).unwrap();
+        assert_eq!(
+            args.rule,
+            Some(RuleArg::Generations(GenerationsRule::brians_brain()))
+        );
+        assert_eq!(
+            "B3/S23/C2".parse(),
+            Ok(RuleArg::Life("B3/S23".parse().unwrap()))
+        );
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn initial_generations_pool() {
+        let args =
+            Args::try_parse_from(["game_of_life", "--rule", "345/2/4", "--width", "10"]).unwrap();
+        let pool = args.initial_generations_pool().unwrap();
+        assert_eq!((pool.width(), pool.height()), (10, 72));
+        assert_eq!(pool.rule().states(), 4);
+        let args = Args::try_parse_from(["game_of_life", "--rule", "B3/S23"]).unwrap();
+        assert_eq!(args.initial_generations_pool(), None);
+        assert_eq!(args.initial_wireworld(), None);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn initial_elementary() {
+        let args =
+            Args::try_parse_from(["game_of_life", "--rule", "W110", "--width", "21"]).unwrap();
+        assert_eq!(
+            args.rule,
+            Some(RuleArg::Elementary(ElementaryRule::new(110)))
+        );
+        let automaton = args.initial_elementary().unwrap();
+        assert_eq!((automaton.width(), automaton.max_history()), (21, 72));
+        assert_eq!(automaton.current().iter().position(|cell| *cell), Some(10));
+        assert_eq!(args.initial_generations_pool(), None);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn initial_margolus_pool() {
+        let args = Args::try_parse_from([
+            "game_of_life",
+            "--rule",
+            "M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15",
+            "--seed",
+            "3",
+        ])
+        .unwrap();
+        let rule = MargolusRule::billiard_ball_machine();
+        assert_eq!(args.rule, Some(RuleArg::Margolus(rule)));
+        let pool = args.initial_margolus_pool().unwrap();
+        let (life, _) = args.initial_pool();
+        assert_eq!(pool, MargolusPool::from_pool(&life, rule));
+        assert_eq!(args.initial_elementary(), None);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn initial_lenia_pool() {
+        let args = Args::try_parse_from([
+            "game_of_life",
+            "--rule",
+            "R=13;T=10;m=0.15;s=0.015",
+            "--seed",
+            "4",
+        ])
+        .unwrap();
+        assert_eq!(args.rule, Some(RuleArg::Lenia(LeniaRule::default())));
+        let pool = args.initial_lenia_pool().unwrap();
+        // The cells alive on the random life board have random values.
+        let (life, _) = args.initial_pool();
+        assert_eq!((pool.width(), pool.height()), (life.width(), life.height()));
+        for row in 0..life.height() {
+            for column in 0..life.width() {
+                assert_eq!(pool.get_cell(row, column) > 0.0, life.get_cell(row, column));
+            }
+        }
+        assert_eq!(args.initial_margolus_pool(), None);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn initial_rule_file_pool() {
+        let args = Args::try_parse_from([
+            "game_of_life",
+            "--rule-file",
+            "rules/WireWorld.rule",
+            "--width",
+            "20",
+            "--height",
+            "10",
+        ])
+        .unwrap();
+        let pool = args.initial_rule_file_pool().unwrap();
+        assert_eq!((pool.rule().name(), pool.rule().states()), ("WireWorld", 4));
+        assert_eq!((pool.width(), pool.height()), (20, 10));
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn initial_wireworld() {
+        let args =
+            Args::try_parse_from(["game_of_life", "--rule", "wireworld", "--height", "9"]).unwrap();
+        assert_eq!(args.rule, Some(RuleArg::WireWorld));
+        assert_eq!(args.initial_wireworld(), Some(WireWorld::new(128, 9)));
+        assert_eq!(args.initial_generations_pool(), None);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn initial_ltl_pool() {
+        let args = Args::try_parse_from([
+            "game_of_life",
+            "--rule",
+            "R5,C0,M1,S34..58,B34..45,NM",
+            "--topology",
+            "torus",
+        ])
+        .unwrap();
+        assert_eq!(args.rule, Some(RuleArg::LargerThanLife(LtlRule::bosco())));
+        let pool = args.initial_ltl_pool().unwrap();
+        assert_eq!(pool.rule(), LtlRule::bosco());
+        assert_eq!(pool.topology(), Topology::Torus);
+        assert_eq!(args.initial_wireworld(), None);
+    }
+
+    #[test]
+    fn reject_invalid_values() {
+        for invalid in [
+            ["--width", "0"],
+            ["--rule", "B3S23"],
+            ["--topology", "sphere"],
+            ["--density", "1.5"],
+            ["--speed", "101"],
+            ["--seed", "-1"],
+        ] {
+            let args = ["game_of_life", invalid[0], invalid[1]];
+            assert!(
+                Args::try_parse_from(args).is_err(),
+                "{:?} accepted",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn soup_search_command() {
+        let args = Args::try_parse_from(["game_of_life", "soup-search", "--soups", "10"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::SoupSearch {
+                seed: 0,
+                soups: 10,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn run_command() {
+        let args = Args::try_parse_from([
+            "game_of_life",
+            "--seed",
+            "1",
+            "run",
+            "--generations",
+            "100",
+            "--format",
+            "plaintext",
+        ])
+        .unwrap();
+        assert_eq!(args.seed, Some(1));
+        assert!(matches!(
+            args.command,
+            Some(Command::Run {
+                generations: 100,
+                output: None,
+                format: Some(Format::Plaintext),
+                stats: None,
+            })
+        ));
+        assert!(Args::try_parse_from(["game_of_life", "run"]).is_err());
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn tui_command() {
+        let args = Args::try_parse_from(["game_of_life", "tui", "--glyphs", "braille"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Tui {
+                glyphs: Glyphs::Braille
+            })
+        ));
+    }
+}
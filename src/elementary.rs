@@ -0,0 +1,308 @@
+use crate::grid::StateGrid;
+use crate::pool::{Pool, RandomFill, Topology};
+use crate::rule::ParseRuleError;
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::{fmt, str::FromStr};
+
+/// A one-dimensional rule given by its Wolfram number, such as rule 30 or rule 110 :
+/// bit `4 * left + 2 * center + right` of the number is the next state of a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ElementaryRule(u8);
+
+impl ElementaryRule {
+    pub fn new(number: u8) -> Self {
+        Self(number)
+    }
+
+    pub fn number(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the new state of a cell given its state and the state of its left and right neighbors.
+    pub fn next_state(self, left: bool, center: bool, right: bool) -> bool {
+        let index = (u8::from(left) << 2) | (u8::from(center) << 1) | u8::from(right);
+        self.0 >> index & 1 != 0
+    }
+}
+
+impl FromStr for ElementaryRule {
+    type Err = ParseRuleError;
+
+    /// Parses a rule in the `W30` notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .strip_prefix(['W', 'w'])
+            .and_then(|number| number.parse().ok())
+            .map(Self)
+            .ok_or_else(|| ParseRuleError::new(s, "W30"))
+    }
+}
+
+impl fmt::Display for ElementaryRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "W{}", self.0)
+    }
+}
+
+/// A row of cells following an elementary rule, with the history of its previous generations.
+///
+/// As a `StateGrid`, it is the space-time diagram of the history : one row per generation,
+/// the current one at the bottom, so that the diagram scrolls up as generations are computed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Elementary {
+    rule: ElementaryRule,
+    /// Behavior of the ends of the row.
+    topology: Topology,
+    /// Previous generations, oldest first, then the current one.
+    history: VecDeque<Vec<bool>>,
+    /// Largest number of generations kept in the history, the current one included.
+    max_history: u32,
+}
+
+impl Elementary {
+    /// Creates a row of dead cells, at most `max_history` generations being kept.
+    pub fn new(width: u32, max_history: u32, rule: ElementaryRule) -> Self {
+        Self {
+            rule,
+            topology: Topology::default(),
+            history: VecDeque::from([vec![false; width as usize]]),
+            max_history: max_history.max(1),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.current().len() as u32
+    }
+
+    pub fn max_history(&self) -> u32 {
+        self.max_history
+    }
+
+    pub fn rule(&self) -> ElementaryRule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: ElementaryRule) {
+        self.rule = rule;
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    /// Returns the cells of the current generation.
+    pub fn current(&self) -> &[bool] {
+        self.history.back().expect("the history is never empty")
+    }
+
+    /// Iterates over the generations of the history, oldest first, the current one being the last.
+    pub fn history(&self) -> impl Iterator<Item = &[bool]> + '_ {
+        self.history.iter().map(Vec::as_slice)
+    }
+
+    /// Returns a cell of the current generation.
+    pub fn get_cell(&self, column: u32) -> bool {
+        self.current()[column as usize]
+    }
+
+    /// Changes a cell of the current generation.
+    pub fn set_cell(&mut self, column: u32, state: bool) {
+        self.current_mut()[column as usize] = state;
+    }
+
+    fn current_mut(&mut self) -> &mut Vec<bool> {
+        self.history.back_mut().expect("the history is never empty")
+    }
+
+    /// Forgets the history and kills every cell.
+    pub fn clear(&mut self) {
+        let width = self.width() as usize;
+        self.history = VecDeque::from([vec![false; width]]);
+    }
+
+    /// Randomizes the cells of the current generation as described by the given fill,
+    /// see `Pool::randomize_with`.
+    pub fn randomize_with(&mut self, fill: &RandomFill) {
+        fill.apply(core::slice::from_mut(self.current_mut()));
+    }
+
+    /// Computes the next generation, forgetting the oldest one if the history is full.
+    pub fn step(&mut self) {
+        let current = self.current();
+        let width = current.len();
+        let cell = |column: usize, offset: isize| match self.topology {
+            Topology::Bounded => column
+                .checked_add_signed(offset)
+                .and_then(|column| current.get(column))
+                .copied()
+                .unwrap_or(false),
+            Topology::Torus => current[(column + width).wrapping_add_signed(offset) % width],
+        };
+        let next = (0..width)
+            .map(|column| {
+                self.rule
+                    .next_state(cell(column, -1), current[column], cell(column, 1))
+            })
+            .collect();
+        if self.history.len() == self.max_history as usize {
+            self.history.pop_front();
+        }
+        self.history.push_back(next);
+    }
+
+    /// Returns the history as a pool, one row per generation, oldest first.
+    pub fn to_pool(&self) -> Pool {
+        let mut pool = Pool::new(self.width(), self.history.len() as u32);
+        for (row, cells) in self.history.iter().enumerate() {
+            for (column, alive) in cells.iter().enumerate() {
+                if *alive {
+                    pool.set_cell(row as u32, column as u32, true);
+                }
+            }
+        }
+        pool
+    }
+
+    /// Returns the generation shown at a row of the space-time diagram, `None` above the oldest one.
+    fn generation_at(&self, row: u32) -> Option<usize> {
+        (row as usize).checked_sub(self.max_history as usize - self.history.len())
+    }
+}
+
+impl StateGrid for Elementary {
+    fn width(&self) -> u32 {
+        Elementary::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        self.max_history
+    }
+
+    fn state_count(&self) -> u8 {
+        2
+    }
+
+    fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.generation_at(row).map_or(0, |generation| {
+            u8::from(self.history[generation][column as usize])
+        })
+    }
+
+    /// Changes a cell of any generation of the diagram, but only the current one,
+    /// in the bottom row, is used to compute the next generations.
+    fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        if let Some(generation) = self.generation_at(row) {
+            self.history[generation][column as usize] = state != 0;
+        }
+    }
+
+    fn step(&mut self) {
+        Elementary::step(self)
+    }
+
+    fn clear(&mut self) {
+        Elementary::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    /// Returns a row of the given width whose center cell is alive.
+    fn single_cell(width: u32, rule: u8) -> Elementary {
+        let mut automaton = Elementary::new(width, 100, ElementaryRule::new(rule));
+        automaton.set_cell(width / 2, true);
+        automaton
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("W30".parse(), Ok(ElementaryRule::new(30)));
+        assert_eq!("w110".parse(), Ok(ElementaryRule::new(110)));
+        assert_eq!(ElementaryRule::new(90).to_string(), "W90");
+        for invalid in ["30", "W256", "W", "B3/S23"] {
+            assert!(invalid.parse::<ElementaryRule>().is_err());
+        }
+    }
+
+    #[test]
+    fn rule_30() {
+        let mut automaton = single_cell(7, 30);
+        for _ in 0..3 {
+            automaton.step();
+        }
+        let rows: Vec<Vec<u8>> = automaton
+            .history()
+            .map(|row| row.iter().map(|cell| u8::from(*cell)).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                [0, 0, 0, 1, 0, 0, 0],
+                [0, 0, 1, 1, 1, 0, 0],
+                [0, 1, 1, 0, 0, 1, 0],
+                [1, 1, 0, 1, 1, 1, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn rule_90_is_sierpinski() {
+        // Generation n of rule 90 has 2 to the power of the number of ones of n alive cells.
+        let mut automaton = single_cell(129, 90);
+        for _ in 0..63 {
+            automaton.step();
+        }
+        for (generation, row) in automaton.history().enumerate() {
+            let population = row.iter().filter(|cell| **cell).count();
+            assert_eq!(population, 1 << generation.count_ones());
+        }
+    }
+
+    #[test]
+    fn torus_wraps() {
+        // Rule 2 moves every cell one column to the left.
+        let mut automaton = Elementary::new(5, 10, ElementaryRule::new(2));
+        automaton.set_cell(0, true);
+        automaton.step();
+        assert!(automaton.current().iter().all(|cell| !cell));
+        automaton.clear();
+        automaton.set_topology(Topology::Torus);
+        automaton.set_cell(0, true);
+        automaton.step();
+        assert_eq!(automaton.current(), [false, false, false, false, true]);
+    }
+
+    #[test]
+    fn space_time_diagram() {
+        let mut automaton = single_cell(9, 30);
+        automaton.max_history = 4;
+        // The current generation is at the bottom of the diagram.
+        assert_eq!(automaton.get_state(3, 4), 1);
+        assert_eq!(automaton.get_state(2, 4), 0);
+        for _ in 0..6 {
+            automaton.step();
+        }
+        assert_eq!(automaton.history().count(), 4);
+        let pool = automaton.to_pool();
+        assert_eq!((pool.width(), pool.height()), (9, 4));
+        for row in 0..4 {
+            for column in 0..9 {
+                assert_eq!(
+                    pool.get_cell(row, column),
+                    automaton.get_state(row, column) == 1
+                );
+            }
+        }
+        assert_eq!(
+            pool.alive_cells().filter(|(row, _)| *row == 3).count(),
+            automaton.current().iter().filter(|cell| **cell).count()
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use crate::grid::{FromSize, Grid};
+#[cfg(feature = "alloc")]
+use crate::pool::Pool;
+use crate::pool::{Area, RandomFill, Topology};
+use crate::rule::Rule;
+use core::{error, fmt, ops};
+
+/// A pool of `WIDTH` by `HEIGHT` cells stored inline, which needs neither the standard library
+/// nor a heap allocator, e.g. to drive a LED matrix from a microcontroller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FixedPool<const WIDTH: usize, const HEIGHT: usize> {
+    /// Alive state of each cell, indexed `[row][column]`, true is alive.
+    state: [[bool; WIDTH]; HEIGHT],
+    /// Behavior of the edges.
+    topology: Topology,
+    /// Numbers of alive neighbors for which cells are born or survive.
+    rule: Rule,
+}
+impl<const WIDTH: usize, const HEIGHT: usize> Default for FixedPool<WIDTH, HEIGHT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<const WIDTH: usize, const HEIGHT: usize> fmt::Display for FixedPool<WIDTH, HEIGHT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.state {
+            for cell in row {
+                write!(f, "{}", if *cell { "O" } else { " " })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+impl<const WIDTH: usize, const HEIGHT: usize> ops::AddAssign for FixedPool<WIDTH, HEIGHT> {
+    fn add_assign(&mut self, other: Self) {
+        for (row, other_row) in self.state.iter_mut().zip(other.state) {
+            for (cell, other_cell) in row.iter_mut().zip(other_row) {
+                *cell = *cell || other_cell;
+            }
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<const WIDTH: usize, const HEIGHT: usize> From<FixedPool<WIDTH, HEIGHT>> for Pool {
+    fn from(fixed: FixedPool<WIDTH, HEIGHT>) -> Self {
+        let mut pool = Pool::from_array(&fixed.state);
+        pool.set_topology(fixed.topology);
+        pool.set_rule(fixed.rule);
+        pool
+    }
+}
+/// Fails when the pool is not `WIDTH` by `HEIGHT`.
+#[cfg(feature = "alloc")]
+impl<const WIDTH: usize, const HEIGHT: usize> TryFrom<&Pool> for FixedPool<WIDTH, HEIGHT> {
+    type Error = SizeMismatch;
+
+    fn try_from(pool: &Pool) -> Result<Self, Self::Error> {
+        let expected = (WIDTH as u32, HEIGHT as u32);
+        let actual = (pool.width(), pool.height());
+        if actual != expected {
+            return Err(SizeMismatch { expected, actual });
+        }
+        let mut fixed = Self::new();
+        for row in 0..pool.height() {
+            for column in 0..pool.width() {
+                fixed.set_cell(row, column, pool.get_cell(row, column));
+            }
+        }
+        fixed.topology = pool.topology();
+        fixed.rule = pool.rule();
+        Ok(fixed)
+    }
+}
+
+/// Error returned when a pool is converted to a `FixedPool` of another size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeMismatch {
+    /// `(width, height)` of the `FixedPool`.
+    pub expected: (u32, u32),
+    /// `(width, height)` of the converted pool.
+    pub actual: (u32, u32),
+}
+impl fmt::Display for SizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected a {}x{} pool, got a {}x{} one",
+            self.expected.0, self.expected.1, self.actual.0, self.actual.1
+        )
+    }
+}
+impl error::Error for SizeMismatch {}
+
+impl<const WIDTH: usize, const HEIGHT: usize> FixedPool<WIDTH, HEIGHT> {
+    pub fn new() -> Self {
+        Self {
+            state: [[false; WIDTH]; HEIGHT],
+            topology: Topology::default(),
+            rule: Rule::default(),
+        }
+    }
+
+    /// Creates a pool from its cells, indexed `[row][column]`.
+    pub fn from_array(state: [[bool; WIDTH]; HEIGHT]) -> Self {
+        Self {
+            state,
+            ..Self::new()
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        WIDTH as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        HEIGHT as u32
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    /// Returns the number of alive cells.
+    pub fn population(&self) -> u32 {
+        self.state.iter().flatten().filter(|cell| **cell).count() as u32
+    }
+
+    /// Returns the smallest area containing every alive cell, `None` if there is none.
+    pub fn bounding_box(&self) -> Option<Area> {
+        let is_alive_row = |row: &usize| self.state[*row].contains(&true);
+        let is_alive_column = |column: &usize| self.state.iter().any(|row| row[*column]);
+        let first_row = (0..HEIGHT).find(is_alive_row)?;
+        let last_row = (0..HEIGHT).rev().find(is_alive_row)?;
+        let first_column = (0..WIDTH).find(is_alive_column)?;
+        let last_column = (0..WIDTH).rev().find(is_alive_column)?;
+        Some(Area {
+            row: first_row as u32,
+            column: first_column as u32,
+            height: (last_row - first_row + 1) as u32,
+            width: (last_column - first_column + 1) as u32,
+        })
+    }
+
+    /// Randomizes the cells as described by the given fill, see `Pool::randomize_with`.
+    pub fn randomize_with(&mut self, fill: &RandomFill) {
+        fill.apply(&mut self.state);
+    }
+
+    pub fn clear(&mut self) {
+        self.state = [[false; WIDTH]; HEIGHT];
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> bool {
+        self.state[row as usize][column as usize]
+    }
+
+    pub fn set_cell(&mut self, row: u32, column: u32, state: bool) {
+        self.state[row as usize][column as usize] = state;
+    }
+
+    /// Computes the next generation. The next state is built on the stack, next to the current one.
+    pub fn step(&mut self) {
+        let mut next_state = [[false; WIDTH]; HEIGHT];
+        for (i, row) in next_state.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                let neighborhood = self.topology.neighborhood(
+                    self.height(),
+                    self.width(),
+                    i as u32,
+                    j as u32,
+                    |i, j| self.state[i as usize][j as usize],
+                );
+                *cell = self.rule.next_state_of(neighborhood);
+            }
+        }
+        self.state = next_state;
+    }
+
+    /// Returns the pool moved by the given offset in a pool of `NEW_WIDTH` by `NEW_HEIGHT`.
+    /// The new pool must be at least as large as this one, which is checked at compile time,
+    /// and cells moved beyond its edges are lost.
+    pub fn with_offset<const NEW_WIDTH: usize, const NEW_HEIGHT: usize>(
+        &self,
+        row_offset: u32,
+        column_offset: u32,
+    ) -> FixedPool<NEW_WIDTH, NEW_HEIGHT> {
+        const {
+            assert!(
+                NEW_WIDTH >= WIDTH && NEW_HEIGHT >= HEIGHT,
+                "the new pool is smaller than the pool"
+            )
+        };
+        let mut result = FixedPool::<NEW_WIDTH, NEW_HEIGHT>::new();
+        result.topology = self.topology;
+        result.rule = self.rule;
+        for (i, row) in self.state.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                let (new_row, new_column) = (row_offset as usize + i, column_offset as usize + j);
+                if new_row < NEW_HEIGHT && new_column < NEW_WIDTH {
+                    result.state[new_row][new_column] = *cell;
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the pool mirrored across its horizontal axis, which flips the rows,
+    /// and/or across its vertical axis, which flips the columns.
+    pub fn mirrored(&self, horizontal: bool, vertical: bool) -> Self {
+        let mut result = *self;
+        if horizontal {
+            result.state.reverse();
+        }
+        if vertical {
+            for row in &mut result.state {
+                row.reverse();
+            }
+        }
+        result
+    }
+
+    /// Returns the pool rotated by 90 degrees clockwise, its width and height being swapped.
+    pub fn rotated_clockwise(&self) -> FixedPool<HEIGHT, WIDTH> {
+        let mut result = FixedPool::<HEIGHT, WIDTH>::new();
+        result.topology = self.topology;
+        result.rule = self.rule;
+        for (i, row) in result.state.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = self.state[HEIGHT - 1 - j][i];
+            }
+        }
+        result
+    }
+}
+
+impl FixedPool<3, 3> {
+    /// Creates a spaceship oriented towards South East.
+    pub fn glider_south_east() -> Self {
+        Self::from_array([
+            [false, true, false],
+            [false, false, true],
+            [true, true, true],
+        ])
+    }
+}
+
+impl FixedPool<7, 3> {
+    /// Creates an acorn.
+    pub fn acorn() -> Self {
+        Self::from_array([
+            [false, true, false, false, false, false, false],
+            [false, false, false, true, false, false, false],
+            [true, true, false, false, true, true, true],
+        ])
+    }
+}
+
+/// Fails when the cells do not fit in `WIDTH` by `HEIGHT`.
+impl<const WIDTH: usize, const HEIGHT: usize> FromSize for FixedPool<WIDTH, HEIGHT> {
+    fn from_size(width: u32, height: u32, rule: Rule) -> Option<Self> {
+        if width as usize > WIDTH || height as usize > HEIGHT {
+            return None;
+        }
+        let mut pool = Self::new();
+        pool.set_rule(rule);
+        Some(pool)
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Grid for FixedPool<WIDTH, HEIGHT> {
+    fn width(&self) -> u32 {
+        WIDTH as u32
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT as u32
+    }
+
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    fn get_cell(&self, row: u32, column: u32) -> bool {
+        FixedPool::get_cell(self, row, column)
+    }
+
+    fn set_cell(&mut self, row: u32, column: u32, state: bool) {
+        FixedPool::set_cell(self, row, column, state)
+    }
+
+    fn step(&mut self) {
+        FixedPool::step(self)
+    }
+
+    fn population(&self) -> u32 {
+        FixedPool::population(self)
+    }
+
+    fn bounding_box(&self) -> Option<Area> {
+        FixedPool::bounding_box(self)
+    }
+
+    fn clear(&mut self) {
+        FixedPool::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step() {
+        let mut pool = FixedPool::<3, 3>::new();
+        pool.set_cell(0, 1, true);
+        pool.set_cell(1, 1, true);
+        pool.set_cell(2, 1, true);
+        pool.step();
+        assert_eq!(pool.population(), 3);
+        assert!(pool.get_cell(1, 0) && pool.get_cell(1, 1) && pool.get_cell(1, 2));
+    }
+
+    #[test]
+    fn glider_crosses_torus() {
+        let mut pool: FixedPool<8, 8> = FixedPool::glider_south_east().with_offset(1, 1);
+        pool.set_topology(Topology::Torus);
+        let start = pool;
+        for _ in 0..4 {
+            pool.step();
+        }
+        let mut moved: FixedPool<8, 8> = FixedPool::glider_south_east().with_offset(2, 2);
+        moved.set_topology(Topology::Torus);
+        assert_eq!(pool, moved);
+        for _ in 0..28 {
+            pool.step();
+        }
+        assert_eq!(pool, start);
+        assert_eq!(
+            pool.bounding_box(),
+            Some(Area {
+                row: 1,
+                column: 1,
+                height: 3,
+                width: 3
+            })
+        );
+    }
+
+    #[test]
+    fn transforms() {
+        let acorn = FixedPool::acorn();
+        let rotated: FixedPool<3, 7> = acorn.rotated_clockwise();
+        assert_eq!(rotated.rotated_clockwise(), acorn.mirrored(true, true));
+        assert_eq!(rotated.population(), acorn.population());
+        assert_eq!(acorn.mirrored(true, false).mirrored(true, false), acorn);
+
+        let mut pool: FixedPool<7, 3> = FixedPool::new();
+        pool += FixedPool::glider_south_east().with_offset(0, 4);
+        pool += acorn;
+        // The bottom rows of the glider and of the acorn overlap on three cells.
+        assert_eq!(pool.population(), 9);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn randomize_like_pool() {
+        let fill = RandomFill::new(7, 0.4).with_symmetry(crate::pool::Symmetry::D4);
+        let mut fixed = FixedPool::<16, 12>::new();
+        fixed.randomize_with(&fill);
+        let mut pool = Pool::new(16, 12);
+        pool.randomize_with(&fill);
+        assert_eq!(Pool::from(fixed), pool);
+        let error = FixedPool::<16, 16>::try_from(&pool).unwrap_err();
+        assert_eq!(
+            error,
+            SizeMismatch {
+                expected: (16, 16),
+                actual: (16, 12)
+            }
+        );
+        assert_eq!(error.to_string(), "expected a 16x16 pool, got a 16x12 one");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn step_like_pool() {
+        for topology in [Topology::Bounded, Topology::Torus] {
+            let mut pool = Pool::new(12, 10);
+            pool.set_topology(topology);
+            pool += Pool::acorn().with_offset(3, 2);
+            let mut fixed = FixedPool::<12, 10>::try_from(&pool).unwrap();
+            assert_eq!(fixed.topology(), topology);
+
+            for _ in 0..20 {
+                pool.step();
+                fixed.step();
+                for row in 0..pool.height() {
+                    for column in 0..pool.width() {
+                        assert_eq!(fixed.get_cell(row, column), pool.get_cell(row, column));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::grid::StateGrid;
+use crate::pool::{compute_next_state, Pool, RandomFill, Topology};
+use crate::rule::{ParseRuleError, Rule};
+use alloc::{format, vec, vec::Vec};
+use core::{fmt, str::FromStr};
+
+/// A Generations rule : alive cells which do not survive go through dying states,
+/// in which they are not counted as neighbors, before becoming dead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GenerationsRule {
+    /// Numbers of alive neighbors for which cells are born or survive.
+    rule: Rule,
+    /// Number of states, dead and alive included.
+    states: u8,
+}
+impl Default for GenerationsRule {
+    /// Conway's game of life, B3/S23/C2.
+    fn default() -> Self {
+        Self::new(Rule::default(), 2)
+    }
+}
+
+impl GenerationsRule {
+    /// State of alive cells, dying states follow it and `0` is the dead state.
+    pub const ALIVE: u8 = 1;
+
+    /// `states` is at least 2, the dead and alive states.
+    pub fn new(rule: Rule, states: u8) -> Self {
+        Self {
+            rule,
+            states: states.max(2),
+        }
+    }
+
+    /// Brian's Brain, /2/3.
+    pub fn brians_brain() -> Self {
+        Self::new(Rule::new(&[2], &[]), 3)
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// Returns the new state of a cell, given its current state and its neighborhood of alive cells,
+    /// see `Rule::next_state_of`.
+    pub fn next_state(&self, current_state: u8, neighborhood: u16) -> u8 {
+        match current_state {
+            0 => u8::from(self.rule.next_state_of(neighborhood & !Rule::CENTER)),
+            Self::ALIVE if self.rule.next_state_of(neighborhood | Rule::CENTER) => Self::ALIVE,
+            dying => (dying + 1) % self.states,
+        }
+    }
+}
+
+impl FromStr for GenerationsRule {
+    type Err = ParseRuleError;
+
+    /// Parses a rule in the `B2/S/C3` notation, the state count being prefixed with `C` or `G`,
+    /// or in the survival first `/2/3` notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRuleError::new(s, "B2/S/C3 or /2/3");
+        let parts: Vec<&str> = s.trim().split('/').collect();
+        if parts.len() != 3 {
+            return Err(error());
+        }
+        let count_index = parts
+            .iter()
+            .position(|part| part.starts_with(['C', 'c', 'G', 'g']))
+            .unwrap_or(2);
+        let count = parts[count_index].trim_start_matches(['C', 'c', 'G', 'g']);
+        let states: u8 = count.parse().map_err(|_| error())?;
+        if states < 2 {
+            return Err(error());
+        }
+        let others: Vec<&str> = (0..3)
+            .filter(|index| *index != count_index)
+            .map(|index| parts[index])
+            .collect();
+        let rule = format!("{}/{}", others[0], others[1])
+            .parse()
+            .map_err(|_| error())?;
+        Ok(Self::new(rule, states))
+    }
+}
+
+impl fmt::Display for GenerationsRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/C{}", self.rule, self.states)
+    }
+}
+
+/// A pool of cells following a Generations rule, each cell storing its state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GenerationsPool {
+    /// State of each cell, indexed `[row][column]`.
+    state: Vec<Vec<u8>>,
+    /// Behavior of the edges.
+    topology: Topology,
+    rule: GenerationsRule,
+}
+
+impl GenerationsPool {
+    pub fn new(width: u32, height: u32, rule: GenerationsRule) -> Self {
+        Self {
+            state: vec![vec![0; width as usize]; height as usize],
+            topology: Topology::default(),
+            rule,
+        }
+    }
+
+    /// Creates a pool of the same size and topology, in which the alive cells of `pool` are alive.
+    pub fn from_pool(pool: &Pool, rule: GenerationsRule) -> Self {
+        let mut result = Self::new(pool.width(), pool.height(), rule);
+        result.topology = pool.topology();
+        for (row, cells) in result.state.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                if pool.get_cell(row as u32, column as u32) {
+                    *cell = GenerationsRule::ALIVE;
+                }
+            }
+        }
+        result
+    }
+
+    pub fn width(&self) -> u32 {
+        self.state.first().map_or(0, |row| row.len() as u32)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.state.len() as u32
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn rule(&self) -> GenerationsRule {
+        self.rule
+    }
+
+    /// Changes the rule, cells in states which do not exist anymore die.
+    pub fn set_rule(&mut self, rule: GenerationsRule) {
+        self.rule = rule;
+        for cell in self.state.iter_mut().flatten() {
+            if *cell >= rule.states() {
+                *cell = 0;
+            }
+        }
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> u8 {
+        self.state[row as usize][column as usize]
+    }
+
+    pub fn set_cell(&mut self, row: u32, column: u32, state: u8) {
+        self.state[row as usize][column as usize] = state;
+    }
+
+    /// Returns the number of alive cells, dying cells excluded.
+    pub fn population(&self) -> u32 {
+        self.state
+            .iter()
+            .flatten()
+            .filter(|cell| **cell == GenerationsRule::ALIVE)
+            .count() as u32
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.state.iter_mut().flatten() {
+            *cell = 0;
+        }
+    }
+
+    /// Randomizes the cells as described by the given fill, see `Pool::randomize_with`.
+    /// Filled cells are either alive or dead.
+    pub fn randomize_with(&mut self, fill: &RandomFill) {
+        let mut alive: Vec<Vec<bool>> = self
+            .state
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| *cell == GenerationsRule::ALIVE)
+                    .collect()
+            })
+            .collect();
+        let before = alive.clone();
+        fill.apply(&mut alive);
+        for ((row, alive_row), before_row) in self.state.iter_mut().zip(alive).zip(before) {
+            for ((cell, alive), before) in row.iter_mut().zip(alive_row).zip(before_row) {
+                if alive != before {
+                    *cell = u8::from(alive);
+                }
+            }
+        }
+    }
+
+    /// Computes the next state of the rows from `start` included to `stop` excluded.
+    fn next_state_rows(&self, start: u32, stop: u32) -> Vec<Vec<u8>> {
+        let (height, width) = (self.height(), self.width());
+        (start..stop)
+            .map(|row| {
+                (0..width)
+                    .map(|column| {
+                        let neighborhood =
+                            self.topology
+                                .neighborhood(height, width, row, column, |i, j| {
+                                    self.state[i as usize][j as usize] == GenerationsRule::ALIVE
+                                });
+                        self.rule
+                            .next_state(self.get_cell(row, column), neighborhood)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Computes the next generation, on several threads like `Pool::step`.
+    pub fn step(&mut self) {
+        self.state = compute_next_state(self.height(), self.width(), |start, stop| {
+            self.next_state_rows(start, stop)
+        });
+    }
+}
+
+impl StateGrid for GenerationsPool {
+    fn width(&self) -> u32 {
+        GenerationsPool::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        GenerationsPool::height(self)
+    }
+
+    fn state_count(&self) -> u8 {
+        self.rule.states()
+    }
+
+    fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.get_cell(row, column)
+    }
+
+    fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        self.set_cell(row, column, state)
+    }
+
+    fn step(&mut self) {
+        GenerationsPool::step(self)
+    }
+
+    fn clear(&mut self) {
+        GenerationsPool::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let brians_brain = GenerationsRule::brians_brain();
+        assert_eq!("/2/3".parse(), Ok(brians_brain));
+        assert_eq!("B2/S/C3".parse(), Ok(brians_brain));
+        assert_eq!("b2/s/g3".parse(), Ok(brians_brain));
+        assert_eq!(
+            "345/2/4".parse(),
+            Ok(GenerationsRule::new(Rule::new(&[2], &[3, 4, 5]), 4))
+        );
+        assert_eq!("B3/S23/C2".parse(), Ok(GenerationsRule::default()));
+        assert!("B2/S".parse::<GenerationsRule>().is_err());
+        assert!("/2/1".parse::<GenerationsRule>().is_err());
+        assert!("/2/256".parse::<GenerationsRule>().is_err());
+        assert_eq!(brians_brain.to_string(), "B2/S/C3");
+    }
+
+    #[test]
+    fn brians_brain_step() {
+        let mut pool = GenerationsPool::new(4, 4, GenerationsRule::brians_brain());
+        pool.set_cell(1, 1, GenerationsRule::ALIVE);
+        pool.set_cell(1, 2, GenerationsRule::ALIVE);
+        pool.step();
+        // Firing cells always start dying, cells next to both of them fire.
+        assert_eq!((pool.get_cell(1, 1), pool.get_cell(1, 2)), (2, 2));
+        for (row, column) in [(0, 1), (0, 2), (2, 1), (2, 2)] {
+            assert_eq!(pool.get_cell(row, column), GenerationsRule::ALIVE);
+        }
+        assert_eq!(pool.population(), 4);
+        pool.step();
+        assert_eq!((pool.get_cell(1, 1), pool.get_cell(1, 2)), (0, 0));
+        assert_eq!(pool.get_cell(0, 1), 2);
+    }
+
+    #[test]
+    fn two_states_is_life() {
+        let mut pool = Pool::new(20, 20);
+        pool += Pool::acorn().with_offset(8, 6);
+        let mut generations = GenerationsPool::from_pool(&pool, GenerationsRule::default());
+        for _ in 0..10 {
+            pool.step();
+            generations.step();
+        }
+        assert_eq!(
+            generations,
+            GenerationsPool::from_pool(&pool, GenerationsRule::default())
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::pool::Area;
+use crate::rule::Rule;
+
+/// A rectangular universe of life-like cells, whatever the way they are stored and stepped.
+///
+/// Rendering and pattern files only go through this trait, so that they work with every engine.
+pub trait Grid {
+    fn width(&self) -> u32;
+
+    fn height(&self) -> u32;
+
+    /// Rule used by `step`.
+    fn rule(&self) -> Rule;
+
+    /// The cell must be inside the grid, see `contains`.
+    fn get_cell(&self, row: u32, column: u32) -> bool;
+
+    /// The cell must be inside the grid, see `contains`.
+    fn set_cell(&mut self, row: u32, column: u32, state: bool);
+
+    /// Computes the next generation.
+    fn step(&mut self);
+
+    /// Whether a cell, possibly given with negative coordinates, is inside the grid.
+    fn contains(&self, row: i64, column: i64) -> bool {
+        (0..self.height() as i64).contains(&row) && (0..self.width() as i64).contains(&column)
+    }
+
+    /// Iterates over the `(row, column)` of the alive cells, row by row.
+    fn alive_cells(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        (0..self.height()).flat_map(move |row| {
+            (0..self.width())
+                .filter(move |column| self.get_cell(row, *column))
+                .map(move |column| (row, column))
+        })
+    }
+
+    /// Returns the number of alive cells.
+    fn population(&self) -> u32 {
+        self.alive_cells().count() as u32
+    }
+
+    /// Returns the smallest area containing every alive cell, `None` if there is none.
+    fn bounding_box(&self) -> Option<Area> {
+        let mut cells = self.alive_cells();
+        let (first_row, first_column) = cells.next()?;
+        let (mut last_row, mut min_column, mut max_column) =
+            (first_row, first_column, first_column);
+        for (row, column) in cells {
+            last_row = row;
+            min_column = min_column.min(column);
+            max_column = max_column.max(column);
+        }
+        Some(Area {
+            row: first_row,
+            column: min_column,
+            height: last_row - first_row + 1,
+            width: max_column - min_column + 1,
+        })
+    }
+
+    fn clear(&mut self) {
+        for row in 0..self.height() {
+            for column in 0..self.width() {
+                self.set_cell(row, column, false);
+            }
+        }
+    }
+}
+
+/// A grid which can be created empty, so that pattern files can be read into it.
+pub trait FromSize: Grid + Sized {
+    /// Returns an empty grid following `rule` in which cells up to `width` by `height` can be set,
+    /// `None` if this kind of grid cannot hold them. The grid may be larger.
+    fn from_size(width: u32, height: u32, rule: Rule) -> Option<Self>;
+}
+
+/// A rectangular universe of cells with more than two states, such as Generations or WireWorld.
+/// State `0` is the empty state.
+pub trait StateGrid {
+    fn width(&self) -> u32;
+
+    fn height(&self) -> u32;
+
+    /// Number of states, every cell is in `0..state_count()`.
+    fn state_count(&self) -> u8;
+
+    /// The cell must be inside the grid.
+    fn get_state(&self, row: u32, column: u32) -> u8;
+
+    /// The cell must be inside the grid and the state below `state_count()`.
+    fn set_state(&mut self, row: u32, column: u32, state: u8);
+
+    /// Computes the next generation.
+    fn step(&mut self);
+
+    fn clear(&mut self) {
+        for row in 0..self.height() {
+            for column in 0..self.width() {
+                self.set_state(row, column, 0);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::pool::{Pool, Topology};
+    use alloc::collections::BTreeSet;
+
+    /// Grid storing only its alive cells, which relies on every default method.
+    struct SparseGrid {
+        width: u32,
+        height: u32,
+        alive: BTreeSet<(u32, u32)>,
+    }
+    impl Grid for SparseGrid {
+        fn width(&self) -> u32 {
+            self.width
+        }
+
+        fn height(&self) -> u32 {
+            self.height
+        }
+
+        fn rule(&self) -> Rule {
+            Rule::default()
+        }
+
+        fn get_cell(&self, row: u32, column: u32) -> bool {
+            self.alive.contains(&(row, column))
+        }
+
+        fn set_cell(&mut self, row: u32, column: u32, state: bool) {
+            if state {
+                self.alive.insert((row, column));
+            } else {
+                self.alive.remove(&(row, column));
+            }
+        }
+
+        fn step(&mut self) {
+            let mut next = BTreeSet::new();
+            for row in 0..self.height {
+                for column in 0..self.width {
+                    let count = Topology::Bounded.count_alive_neighbors(
+                        self.height,
+                        self.width,
+                        row,
+                        column,
+                        |i, j| self.get_cell(i, j),
+                    );
+                    if self.rule().next_state(self.get_cell(row, column), count) == Some(true) {
+                        next.insert((row, column));
+                    }
+                }
+            }
+            self.alive = next;
+        }
+    }
+
+    impl FromSize for SparseGrid {
+        fn from_size(width: u32, height: u32, _rule: Rule) -> Option<Self> {
+            Some(Self {
+                width,
+                height,
+                alive: BTreeSet::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn default_methods() {
+        let mut pool = Pool::new(8, 6);
+        pool += Pool::glider_south_east().with_offset(1, 2);
+        let mut grid = SparseGrid {
+            width: 8,
+            height: 6,
+            alive: pool.alive_cells().collect(),
+        };
+
+        assert!(grid.contains(0, 0) && !grid.contains(-1, 0) && !grid.contains(0, 8));
+        assert_eq!(
+            grid.alive_cells().collect::<Vec<_>>(),
+            [(1, 3), (2, 4), (3, 2), (3, 3), (3, 4)]
+        );
+        assert_eq!(grid.population(), 5);
+        assert_eq!(grid.bounding_box(), pool.bounding_box());
+        for _ in 0..4 {
+            grid.step();
+            pool.step();
+        }
+        assert_eq!(
+            grid.alive_cells().collect::<Vec<_>>(),
+            pool.alive_cells().collect::<Vec<_>>()
+        );
+        grid.clear();
+        assert_eq!(grid.bounding_box(), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_pattern() {
+        let grid: SparseGrid = crate::pattern::parse_rle("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        assert_eq!((grid.width, grid.height), (3, 3));
+        assert_eq!(
+            grid.alive_cells().collect::<Vec<_>>(),
+            Pool::glider_south_east().alive_cells().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,642 @@
+use graphics::types::{Color, Scalar};
+use graphics::Context;
+use piston::{Key, MouseButton};
+use piston_window::G2d;
+
+use crate::viewport::Viewport;
+use crate::window::{Frame, Scene};
+use game_of_life::grid::Grid;
+use game_of_life::history::{Change, History};
+use game_of_life::pool::{Area, Pool, RandomFill, Symmetry};
+use game_of_life::rewind::Timeline;
+use game_of_life::rule::Neighborhood;
+use game_of_life::stats::Statistics;
+
+#[derive(PartialEq, Eq, Default)]
+enum SelectedPoolStructure {
+    #[default]
+    None,
+    Glider,
+    Acorn,
+}
+
+#[derive(PartialEq, Eq)]
+enum ChangeKind {
+    /// Cells set, killed or stamped by the user, cleared or randomized pool.
+    Edit,
+    /// Generations computed while running.
+    Steps,
+}
+
+/// Pool state before modifications which are not yet in the history.
+struct PendingChange {
+    pool: Pool,
+    generation: u64,
+    kind: ChangeKind,
+}
+
+pub struct App {
+    pool: Pool,
+    selected_pool_structure: SelectedPoolStructure,
+    /// Seed and density of the last randomization, so that it can be replayed.
+    random_fill: RandomFill,
+    statistics: Statistics,
+    render_graph: bool,
+    /// Number of steps since the pool was last cleared or randomized.
+    generation: u64,
+    /// Digits typed so far for the "go to generation" action, `None` when not typing.
+    generation_input: Option<String>,
+    history: History,
+    pending_change: Option<PendingChange>,
+    ctrl_pressed: bool,
+    /// Recent generations, to step backward through them.
+    timeline: Timeline,
+    /// Whether the mouse button was pressed on the timeline scrubber.
+    scrubbing: bool,
+}
+impl Default for App {
+    fn default() -> Self {
+        App::new(128, 72)
+    }
+}
+
+impl App {
+    const DENSITY_STEP: f64 = 0.05;
+    const MAX_STATISTICS: usize = 100_000;
+    const STATISTICS_FILE: &'static str = "statistics.csv";
+    const MAX_HISTORY_BYTES: usize = 64 * 1024 * 1024;
+    const MAX_TIMELINE_GENERATIONS: usize = 1000;
+
+    pub fn new(width: u32, height: u32) -> App {
+        let mut pool: Pool = Pool::new(width, height);
+        let random_fill = RandomFill::new(pool.randomize(), RandomFill::default().density);
+        Self::with_pool(pool, random_fill)
+    }
+
+    /// Shows the given pool, `random_fill` being used when the pool is randomized again.
+    pub fn with_pool(pool: Pool, random_fill: RandomFill) -> App {
+        Self {
+            pool,
+            selected_pool_structure: Default::default(),
+            random_fill,
+            statistics: Statistics::new(Self::MAX_STATISTICS),
+            render_graph: true,
+            generation: 0,
+            generation_input: None,
+            history: History::new(Self::MAX_HISTORY_BYTES),
+            pending_change: None,
+            ctrl_pressed: false,
+            timeline: Timeline::new(Self::MAX_TIMELINE_GENERATIONS, 0),
+            scrubbing: false,
+        }
+    }
+
+    fn get_selected_pool(&self) -> Pool {
+        match self.selected_pool_structure {
+            SelectedPoolStructure::None => Pool::from_array(&[[true]]),
+            SelectedPoolStructure::Glider => Pool::glider_south_east(),
+            SelectedPoolStructure::Acorn => Pool::acorn(),
+        }
+    }
+
+    /// Draws the pool, the structure about to be stamped under the cursor, the graph and the timeline.
+    fn draw_pool(&self, viewport: &Viewport, cursor: [f64; 2], c: Context, g: &mut G2d) {
+        use graphics::*;
+
+        const LIFE_COLOR: Color = [0.0, 0.0, 0.0, 1.0];
+        const DEAD_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+        const HINT_COLOR: Color = [0.0, 0.0, 0.0, 0.5];
+
+        // Draw the pool area. Its rows are shifted in hexagonal mode, so its corners are drawn as a polygon.
+        let (height, width) = (self.pool.height() as i64, self.pool.width() as i64);
+        polygon(
+            DEAD_COLOR,
+            &[
+                viewport.cell_to_screen(0, 0),
+                viewport.cell_to_screen(0, width),
+                viewport.cell_to_screen(height, width),
+                viewport.cell_to_screen(height, 0),
+            ],
+            c.transform,
+            g,
+        );
+
+        // Draw a square for each visible living cell
+        Self::draw_cells(&self.pool, (0, 0), LIFE_COLOR, viewport, c, g);
+
+        // Draw pixels about to be drawn with transparency
+        if let Some(position) = viewport.cell_at(cursor, self.pool.height(), self.pool.width()) {
+            Self::draw_cells(
+                &self.get_selected_pool(),
+                position,
+                HINT_COLOR,
+                viewport,
+                c,
+                g,
+            );
+        }
+
+        if self.render_graph {
+            Self::draw_statistics_graph(&self.statistics, c, g);
+        }
+
+        Self::draw_timeline(&self.timeline, c, g);
+    }
+
+    /// Draws the alive cells of a grid whose top left corner is at the given `(row, column)`,
+    /// skipping the cells outside of the view.
+    fn draw_cells<T: Grid, G: graphics::Graphics>(
+        grid: &T,
+        (row_offset, column_offset): (u32, u32),
+        color: Color,
+        viewport: &Viewport,
+        c: graphics::Context,
+        g: &mut G,
+    ) {
+        let cell_size = viewport.cell_size();
+        let (rows, columns) = viewport.visible_cells(
+            c.get_view_size(),
+            row_offset + grid.height(),
+            column_offset + grid.width(),
+        );
+        for i in rows.start.max(row_offset)..rows.end {
+            for j in columns.start.max(column_offset)..columns.end {
+                if grid.get_cell(i - row_offset, j - column_offset) {
+                    if viewport.is_hexagonal() {
+                        let hexagon = viewport.hexagon(i as i64, j as i64);
+                        graphics::polygon(color, &hexagon, c.transform, g);
+                    } else {
+                        let [x, y] = viewport.cell_to_screen(i as i64, j as i64);
+                        graphics::rectangle(color, [x, y, cell_size, cell_size], c.transform, g);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the position of the timeline scrubber, as `[x, y, width, height]`,
+    /// at the bottom of the view and left of the statistics graph.
+    fn timeline_rectangle(view_size: [Scalar; 2]) -> [Scalar; 4] {
+        const HEIGHT: Scalar = 12.0;
+        const MARGIN: Scalar = 10.0;
+        const RIGHT_SPACE: Scalar = 320.0;
+        let width = (view_size[0] - MARGIN - RIGHT_SPACE).max(0.0);
+        [MARGIN, view_size[1] - MARGIN - HEIGHT, width, HEIGHT]
+    }
+
+    /// Draws the recorded generations as a bar, with a marker on the current one.
+    fn draw_timeline<G: graphics::Graphics>(timeline: &Timeline, c: graphics::Context, g: &mut G) {
+        use graphics::*;
+
+        const BACKGROUND_COLOR: Color = [0.8, 0.8, 0.8, 0.8];
+        const RECORDED_COLOR: Color = [0.4, 0.4, 0.9, 0.8];
+        const MARKER_COLOR: Color = [0.9, 0.1, 0.1, 1.0];
+
+        let [left, top, width, height] = Self::timeline_rectangle(c.get_view_size());
+        rectangle(BACKGROUND_COLOR, [left, top, width, height], c.transform, g);
+        let recorded = timeline.last_generation() - timeline.first_generation();
+        if recorded == 0 {
+            return;
+        }
+        let current = timeline.current_generation() - timeline.first_generation();
+        let marker = left + width * current as Scalar / recorded as Scalar;
+        rectangle(
+            RECORDED_COLOR,
+            [left, top, marker - left, height],
+            c.transform,
+            g,
+        );
+        line(
+            MARKER_COLOR,
+            1.5,
+            [marker, top - 2.0, marker, top + height + 2.0],
+            c.transform,
+            g,
+        );
+    }
+
+    /// Draws the recent population, births and deaths as sparklines in the bottom right corner.
+    fn draw_statistics_graph<G: graphics::Graphics>(
+        statistics: &Statistics,
+        c: graphics::Context,
+        g: &mut G,
+    ) {
+        use graphics::*;
+
+        const BACKGROUND_COLOR: Color = [1.0, 1.0, 1.0, 0.8];
+        const BORDER_COLOR: Color = [0.0, 0.0, 0.0, 1.0];
+        const POPULATION_COLOR: Color = [0.1, 0.1, 0.9, 1.0];
+        const BIRTHS_COLOR: Color = [0.1, 0.7, 0.1, 1.0];
+        const DEATHS_COLOR: Color = [0.9, 0.1, 0.1, 1.0];
+        const GRAPH_WIDTH: Scalar = 300.0;
+        const GRAPH_HEIGHT: Scalar = 100.0;
+        const MARGIN: Scalar = 10.0;
+        // One point per pixel.
+        const POINTS: usize = GRAPH_WIDTH as usize;
+
+        let [view_width, view_height] = c.get_view_size();
+        let left = view_width - GRAPH_WIDTH - MARGIN;
+        let top = view_height - GRAPH_HEIGHT - MARGIN;
+        Rectangle::new(BACKGROUND_COLOR)
+            .border(rectangle::Border {
+                color: BORDER_COLOR,
+                radius: 0.5,
+            })
+            .draw(
+                [left, top, GRAPH_WIDTH, GRAPH_HEIGHT],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+
+        let records: Vec<_> = statistics.last(POINTS).collect();
+        let max_value = records
+            .iter()
+            .map(|record| record.population.max(record.births).max(record.deaths))
+            .max()
+            .unwrap_or(0)
+            .max(1) as Scalar;
+        let series = [
+            (
+                POPULATION_COLOR,
+                records.iter().map(|r| r.population).collect::<Vec<_>>(),
+            ),
+            (BIRTHS_COLOR, records.iter().map(|r| r.births).collect()),
+            (DEATHS_COLOR, records.iter().map(|r| r.deaths).collect()),
+        ];
+        for (color, values) in series {
+            for (x, pair) in values.windows(2).enumerate() {
+                let y0 = top + GRAPH_HEIGHT * (1.0 - pair[0] as Scalar / max_value);
+                let y1 = top + GRAPH_HEIGHT * (1.0 - pair[1] as Scalar / max_value);
+                line(
+                    color,
+                    0.5,
+                    [left + x as Scalar, y0, left + x as Scalar + 1.0, y1],
+                    c.transform,
+                    g,
+                );
+            }
+        }
+    }
+
+    /// Computes the next generation of the pool, or replays it if it was already recorded.
+    fn step(&mut self) {
+        self.begin_change(ChangeKind::Steps);
+        if !self.timeline.forward(&mut self.pool) {
+            let previous = self.pool.clone();
+            self.pool.step();
+            self.statistics
+                .record(self.generation + 1, &previous, &self.pool);
+            self.timeline.record(&previous, &self.pool);
+        }
+        self.generation += 1;
+    }
+
+    /// Goes back to the previous recorded generation.
+    fn step_back(&mut self) {
+        self.begin_change(ChangeKind::Steps);
+        if self.timeline.back(&mut self.pool) {
+            self.generation -= 1;
+        }
+    }
+
+    /// Moves to the recorded generation under the cursor on the timeline.
+    fn scrub(&mut self, frame: &mut Frame) {
+        let [left, _, width, _] = Self::timeline_rectangle(frame.view_size());
+        let fraction = ((frame.cursor()[0] - left) / width).clamp(0.0, 1.0);
+        let first = self.timeline.first_generation();
+        let recorded = self.timeline.last_generation() - first;
+        let target = first + (fraction * recorded as f64).round() as u64;
+
+        frame.set_paused(true);
+        self.begin_change(ChangeKind::Steps);
+        self.timeline.seek(&mut self.pool, target);
+        self.generation = self.timeline.current_generation();
+    }
+
+    /// Whether the cursor is over the timeline scrubber.
+    fn cursor_on_timeline(frame: &Frame) -> bool {
+        let [left, top, width, height] = Self::timeline_rectangle(frame.view_size());
+        let [x, y] = frame.cursor();
+        x >= left && x <= left + width && y >= top && y <= top + height
+    }
+
+    /// Goes to the given generation, replaying it if it was recorded or computing it otherwise.
+    /// Generations in the past can only be reached if they are still in the timeline.
+    fn go_to_generation(&mut self, frame: &mut Frame, target: u64) {
+        if target < self.generation && target < self.timeline.first_generation() {
+            frame.show_message(format!(
+                "Cannot go back to generation {}, the oldest recorded one is {}",
+                target,
+                self.timeline.first_generation()
+            ));
+            return;
+        }
+        self.begin_change(ChangeKind::Steps);
+        self.timeline.seek(&mut self.pool, target);
+        self.generation = self.timeline.current_generation();
+        if self.generation < target {
+            // Intermediate generations are neither drawn nor recorded, only the last step is.
+            while self.generation + 1 < target {
+                self.pool.step();
+                self.generation += 1;
+            }
+            let previous = self.pool.clone();
+            self.pool.step();
+            self.generation = target;
+            self.statistics.record(target, &previous, &self.pool);
+            self.timeline.reset(target - 1);
+            self.timeline.record(&previous, &self.pool);
+        }
+    }
+
+    /// Starts recording modifications of the given kind, unless they are already being recorded.
+    /// Consecutive modifications of the same kind are undone together.
+    fn begin_change(&mut self, kind: ChangeKind) {
+        if let Some(pending_change) = &self.pending_change {
+            if pending_change.kind == kind {
+                return;
+            }
+            self.commit_change();
+        }
+        self.pending_change = Some(PendingChange {
+            pool: self.pool.clone(),
+            generation: self.generation,
+            kind,
+        });
+    }
+
+    /// Adds the modifications recorded since `begin_change` to the history.
+    fn commit_change(&mut self) {
+        if let Some(pending_change) = self.pending_change.take() {
+            self.history.push(Change::between(
+                &pending_change.pool,
+                pending_change.generation,
+                &self.pool,
+                self.generation,
+            ));
+        }
+    }
+
+    /// Reverts the last change, pausing the simulation so that the result can be seen.
+    fn undo(&mut self, frame: &mut Frame) {
+        self.commit_change();
+        if let Some(generation) = self.history.undo(&mut self.pool) {
+            self.generation = generation;
+            self.timeline.reset(generation);
+            frame.set_paused(true);
+        }
+    }
+
+    fn redo(&mut self, frame: &mut Frame) {
+        self.commit_change();
+        if let Some(generation) = self.history.redo(&mut self.pool) {
+            self.generation = generation;
+            self.timeline.reset(generation);
+            frame.set_paused(true);
+        }
+    }
+
+    /// Starts a new run from generation zero, after the pool was cleared or randomized.
+    fn restart(&mut self) {
+        self.generation = 0;
+        self.statistics.clear();
+        self.timeline.reset(0);
+    }
+
+    /// Handles keys while typing the generation to go to.
+    fn process_generation_input(&mut self, frame: &mut Frame, key: Key) {
+        let Some(input) = self.generation_input.as_mut() else {
+            return;
+        };
+        let digit = match key {
+            Key::D0 | Key::NumPad0 => Some('0'),
+            Key::D1 | Key::NumPad1 => Some('1'),
+            Key::D2 | Key::NumPad2 => Some('2'),
+            Key::D3 | Key::NumPad3 => Some('3'),
+            Key::D4 | Key::NumPad4 => Some('4'),
+            Key::D5 | Key::NumPad5 => Some('5'),
+            Key::D6 | Key::NumPad6 => Some('6'),
+            Key::D7 | Key::NumPad7 => Some('7'),
+            Key::D8 | Key::NumPad8 => Some('8'),
+            Key::D9 | Key::NumPad9 => Some('9'),
+            _ => None,
+        };
+        match (key, digit) {
+            (_, Some(digit)) => input.push(digit),
+            (Key::Backspace, _) => {
+                input.pop();
+            }
+            (Key::Return | Key::NumPadEnter, _) => {
+                if let Some(target) = self.generation_input.take().and_then(|i| i.parse().ok()) {
+                    self.go_to_generation(frame, target);
+                }
+            }
+            // J : cancel
+            (Key::J, _) => self.generation_input = None,
+            _ => {}
+        }
+    }
+
+    /// Sets or kills cells depending on the button currently pressed on the mouse.
+    fn handle_pressed_mouse(&mut self, frame: &mut Frame, pressed_button: MouseButton) {
+        if self.scrubbing {
+            self.scrub(frame);
+            return;
+        }
+        let Some((row, column)) = frame.cursor_cell(self.pool.height(), self.pool.width()) else {
+            return;
+        };
+        if matches!(pressed_button, MouseButton::Left | MouseButton::Right) {
+            self.begin_change(ChangeKind::Edit);
+            // The edited pool does not follow from the recorded generations anymore.
+            self.timeline.reset(self.generation);
+        }
+        match pressed_button {
+            MouseButton::Left => {
+                let struct_to_add = self.get_selected_pool();
+                self.pool += struct_to_add.with_offset(row, column)
+            }
+            MouseButton::Right => self.pool.set_cell(row, column, false),
+            _ => {}
+        }
+    }
+
+    /// Select given pool structure to be drawn if not already selected.
+    /// If already selected deselects it.
+    fn select_or_deselect_pool(&mut self, selected_pool: SelectedPoolStructure) {
+        self.selected_pool_structure = if self.selected_pool_structure == selected_pool {
+            SelectedPoolStructure::None
+        } else {
+            selected_pool
+        }
+    }
+
+    /// Handles the keys of the pool window, returns `false` for the keys handled by the frame.
+    fn process_keyboard(&mut self, frame: &mut Frame, key: Key) -> bool {
+        if self.generation_input.is_some() {
+            self.process_generation_input(frame, key);
+            return true;
+        }
+        match key {
+            // , / . : step backward / forward, pausing the simulation
+            Key::Comma => {
+                frame.set_paused(true);
+                self.step_back();
+            }
+            Key::Period => {
+                frame.set_paused(true);
+                self.step();
+            }
+            // Ctrl : modifier for undo and redo
+            Key::LCtrl | Key::RCtrl => self.ctrl_pressed = true,
+            // Ctrl+Z : undo
+            Key::Z if self.ctrl_pressed => self.undo(frame),
+            // Ctrl+Y : redo
+            Key::Y if self.ctrl_pressed => self.redo(frame),
+            // Del : Clear pool
+            Key::Delete => {
+                self.begin_change(ChangeKind::Edit);
+                self.pool.clear();
+                self.restart();
+                self.commit_change();
+            }
+            // R : Randomize pool with a new seed
+            Key::R => {
+                self.begin_change(ChangeKind::Edit);
+                self.random_fill.seed = rand::random();
+                self.pool.randomize_with(&self.random_fill);
+                self.restart();
+                self.commit_change();
+            }
+            // E : Replay the last randomization
+            Key::E => {
+                self.begin_change(ChangeKind::Edit);
+                self.pool.randomize_with(&self.random_fill);
+                self.restart();
+                self.commit_change();
+            }
+            // J : go to generation, the number is typed next
+            Key::J => self.generation_input = Some(String::new()),
+            // X : toggle hexagonal cells
+            Key::X => {
+                let viewport = frame.viewport_mut();
+                viewport.set_hexagonal(!viewport.is_hexagonal());
+            }
+            // G : toggle statistics graph
+            Key::G => self.render_graph = !self.render_graph,
+            // C : export statistics
+            Key::C => frame.show_message(match self.statistics.save_csv(Self::STATISTICS_FILE) {
+                Ok(()) => format!("Statistics exported to {}", Self::STATISTICS_FILE),
+                Err(error) => format!("Cannot export statistics : {}", error),
+            }),
+            // S : cycle through symmetries of the next randomization
+            Key::S => {
+                let current = Symmetry::ALL
+                    .iter()
+                    .position(|symmetry| *symmetry == self.random_fill.symmetry)
+                    .unwrap_or(0);
+                self.random_fill.symmetry = Symmetry::ALL[(current + 1) % Symmetry::ALL.len()];
+            }
+            // Up / Down : modify density of the next randomization
+            Key::Up => {
+                self.random_fill.density = (self.random_fill.density + Self::DENSITY_STEP).min(1.0)
+            }
+            Key::Down => {
+                self.random_fill.density = (self.random_fill.density - Self::DENSITY_STEP).max(0.0)
+            }
+            // 1 : select glider
+            Key::NumPad1 => self.select_or_deselect_pool(SelectedPoolStructure::Glider),
+            // 2 : select acorn
+            Key::NumPad2 => self.select_or_deselect_pool(SelectedPoolStructure::Acorn),
+
+            // TODO : add a rotated field for the selected pool structure, to be able to put glider in other directions
+
+            // Other keys are handled by the frame
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Scene for App {
+    fn width(&self) -> u32 {
+        self.pool.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.pool.height()
+    }
+
+    fn hexagonal(&self) -> bool {
+        self.pool.rule().neighborhood() == Neighborhood::Hexagonal
+    }
+
+    fn step(&mut self, _frame: &mut Frame) {
+        App::step(self)
+    }
+
+    fn draw(&self, viewport: &Viewport, cursor: [f64; 2], c: Context, g: &mut G2d) {
+        self.draw_pool(viewport, cursor, c, g)
+    }
+
+    fn help_lines(&self) -> Vec<String> {
+        vec![
+            match &self.generation_input {
+                Some(input) => format!("Go to generation : {}_", input),
+                None => format!("Generation : {}", self.generation),
+            },
+            "J : go to generation (type number, Enter)".to_owned(),
+            "Left click : set cell".to_owned(),
+            "Right click : kill cell".to_owned(),
+            "del : clear screen".to_owned(),
+            "Ctrl+Z / Ctrl+Y : undo / redo".to_owned(),
+            ", . : step backward / forward".to_owned(),
+            "Click timeline : rewind".to_owned(),
+            "X : toggle hexagonal cells".to_owned(),
+            format!("R : randomize (seed {})", self.random_fill.seed),
+            "E : replay seed".to_owned(),
+            format!("S : symmetry : {}", self.random_fill.symmetry),
+            format!("↑ ↓ : density : {:.0}%", self.random_fill.density * 100.0),
+            "1-2 : select structure".to_owned(),
+            "G : toggle graph".to_owned(),
+            format!("C : export statistics to {}", Self::STATISTICS_FILE),
+        ]
+    }
+
+    /// The alive cells, or the whole pool if there is none.
+    fn fit_area(&self) -> Area {
+        self.pool.bounding_box().unwrap_or(Area {
+            row: 0,
+            column: 0,
+            height: self.pool.height(),
+            width: self.pool.width(),
+        })
+    }
+
+    fn key_press(&mut self, frame: &mut Frame, key: Key) -> bool {
+        self.process_keyboard(frame, key)
+    }
+
+    fn key_release(&mut self, key: Key) {
+        if let Key::LCtrl | Key::RCtrl = key {
+            self.ctrl_pressed = false;
+        }
+    }
+
+    fn mouse_press(&mut self, frame: &mut Frame, button: MouseButton) {
+        self.scrubbing = button == MouseButton::Left && Self::cursor_on_timeline(frame);
+    }
+
+    fn mouse_release(&mut self, _button: MouseButton) {
+        self.scrubbing = false;
+        // A whole drag is undone at once.
+        if matches!(&self.pending_change, Some(change) if change.kind == ChangeKind::Edit) {
+            self.commit_change();
+        }
+    }
+
+    fn mouse_held(&mut self, frame: &mut Frame, button: MouseButton) {
+        self.handle_pressed_mouse(frame, button)
+    }
+}
@@ -0,0 +1,182 @@
+use crate::pool::Pool;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+/// A reversible modification of a pool, stored as the cells it toggled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// Cells whose state differs between before and after the change.
+    toggled_cells: Vec<(u32, u32)>,
+    generation_before: u64,
+    generation_after: u64,
+}
+
+impl Change {
+    /// Computes the change turning `before` into `after`, both pools must have the same size.
+    pub fn between(
+        before: &Pool,
+        generation_before: u64,
+        after: &Pool,
+        generation_after: u64,
+    ) -> Self {
+        let mut toggled_cells = Vec::new();
+        for row in 0..after.height() {
+            for column in 0..after.width() {
+                if before.get_cell(row, column) != after.get_cell(row, column) {
+                    toggled_cells.push((row, column));
+                }
+            }
+        }
+        Self {
+            toggled_cells,
+            generation_before,
+            generation_after,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toggled_cells.is_empty() && self.generation_before == self.generation_after
+    }
+
+    /// Approximate memory used by the change.
+    fn size(&self) -> usize {
+        size_of::<Self>() + self.toggled_cells.len() * size_of::<(u32, u32)>()
+    }
+
+    /// Toggles the cells of the change, which both applies and reverts it.
+    pub fn toggle(&self, pool: &mut Pool) {
+        for (row, column) in &self.toggled_cells {
+            pool.set_cell(*row, *column, !pool.get_cell(*row, *column));
+        }
+    }
+}
+
+/// Undo and redo stacks of changes, keeping at most a given amount of memory.
+/// The oldest changes are forgotten first when the budget is exceeded.
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    max_bytes: usize,
+    used_bytes: usize,
+}
+
+impl History {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            max_bytes,
+            used_bytes: 0,
+        }
+    }
+
+    /// Adds a change which was just applied, this forgets every undone change.
+    pub fn push(&mut self, change: Change) {
+        if change.is_empty() {
+            return;
+        }
+        self.used_bytes -= self.redo.drain(..).map(|c| c.size()).sum::<usize>();
+        self.used_bytes += change.size();
+        self.undo.push_back(change);
+        // Always keep the last change, even if it alone exceeds the budget.
+        while self.used_bytes > self.max_bytes && self.undo.len() > 1 {
+            let forgotten = self.undo.pop_front().unwrap();
+            self.used_bytes -= forgotten.size();
+        }
+    }
+
+    /// Reverts the last change on the pool and returns the generation it had before the change.
+    pub fn undo(&mut self, pool: &mut Pool) -> Option<u64> {
+        let change = self.undo.pop_back()?;
+        change.toggle(pool);
+        let generation = change.generation_before;
+        self.redo.push(change);
+        Some(generation)
+    }
+
+    /// Applies again the last undone change and returns the generation it had after the change.
+    pub fn redo(&mut self, pool: &mut Pool) -> Option<u64> {
+        let change = self.redo.pop()?;
+        change.toggle(pool);
+        let generation = change.generation_after;
+        self.undo.push_back(change);
+        Some(generation)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_redo() {
+        let mut history = History::new(1 << 20);
+        let mut pool = Pool::new(4, 4);
+        let initial = pool.clone();
+
+        pool.set_cell(1, 1, true);
+        pool.set_cell(2, 3, true);
+        let edited = pool.clone();
+        history.push(Change::between(&initial, 0, &pool, 0));
+
+        pool.step();
+        let stepped = pool.clone();
+        history.push(Change::between(&edited, 0, &pool, 1));
+
+        assert_eq!(history.undo(&mut pool), Some(0));
+        assert_eq!(pool, edited);
+        assert_eq!(history.undo(&mut pool), Some(0));
+        assert_eq!(pool, initial);
+        assert_eq!(history.undo(&mut pool), None);
+
+        assert_eq!(history.redo(&mut pool), Some(0));
+        assert_eq!(pool, edited);
+        assert_eq!(history.redo(&mut pool), Some(1));
+        assert_eq!(pool, stepped);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn push_forgets_redo() {
+        let mut history = History::new(1 << 20);
+        let mut pool = Pool::new(4, 4);
+        let initial = pool.clone();
+        pool.set_cell(0, 0, true);
+        history.push(Change::between(&initial, 0, &pool, 0));
+        history.undo(&mut pool);
+        assert!(history.can_redo());
+
+        pool.set_cell(3, 3, true);
+        history.push(Change::between(&initial, 0, &pool, 0));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn budget_forgets_oldest() {
+        let mut pool = Pool::new(10, 10);
+        let mut changes = Vec::new();
+        for i in 0..10 {
+            let before = pool.clone();
+            pool.set_cell(i, i, true);
+            changes.push(Change::between(&before, 0, &pool, 0));
+        }
+        let mut history = History::new(3 * changes[0].size());
+        for change in changes {
+            history.push(change);
+        }
+        for _ in 0..3 {
+            assert!(history.undo(&mut pool).is_some());
+        }
+        assert_eq!(history.undo(&mut pool), None);
+        assert_eq!(pool.population(), 7);
+    }
+}
@@ -0,0 +1,585 @@
+use crate::grid::StateGrid;
+use crate::pool::{compute_next_state, Pool, Topology};
+use crate::rule::ParseRuleError;
+use alloc::{vec, vec::Vec};
+use core::{fmt, ops::RangeInclusive, str::FromStr};
+
+/// Cells around a cell, within the radius of a Larger than Life rule, which are its neighbors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    /// The square of side `2 * radius + 1`.
+    #[default]
+    Moore,
+    /// The diamond of cells at a Manhattan distance of at most `radius`.
+    VonNeumann,
+    /// The disk of cells at a Euclidean distance of at most `radius`.
+    Circular,
+}
+
+impl Neighborhood {
+    /// Returns the number of columns on each side of the center column
+    /// which are in the neighborhood, on the row at `row_offset` from the center.
+    fn half_width(self, radius: u32, row_offset: u32) -> u32 {
+        match self {
+            Neighborhood::Moore => radius,
+            Neighborhood::VonNeumann => radius - row_offset,
+            Neighborhood::Circular => {
+                // Golly's convention, x² + y² <= r² + r, which rounds the disc outward.
+                let remaining = radius * radius + radius - row_offset * row_offset;
+                // Integer square root, the neighborhood being small enough to search it.
+                (0..=radius)
+                    .take_while(|column| column * column <= remaining)
+                    .last()
+                    .unwrap_or(0)
+            }
+        }
+    }
+
+    fn letter(self) -> char {
+        match self {
+            Neighborhood::Moore => 'M',
+            Neighborhood::VonNeumann => 'N',
+            Neighborhood::Circular => 'C',
+        }
+    }
+}
+
+/// A Larger than Life rule : neighbors are counted within a radius,
+/// cells being born or surviving when their count is in a range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LtlRule {
+    radius: u32,
+    /// Number of states, more than 2 adding dying states as in Generations rules.
+    states: u8,
+    /// Whether the cell itself is counted among its neighbors.
+    include_center: bool,
+    /// Smallest and largest counts for which an alive cell survives.
+    survival: (u32, u32),
+    /// Smallest and largest counts for which a dead cell becomes alive.
+    birth: (u32, u32),
+    neighborhood: Neighborhood,
+}
+
+impl LtlRule {
+    pub const MAX_RADIUS: u32 = 500;
+
+    /// Bosco's rule, R5,C0,M1,S34..58,B34..45,NM.
+    pub fn bosco() -> Self {
+        Self::new(5, 34..=58, 34..=45)
+            .with_center(true)
+            .with_neighborhood(Neighborhood::Moore)
+    }
+
+    /// A two states rule on the Moore neighborhood, the center not being counted.
+    /// The radius is between 1 and `MAX_RADIUS`.
+    pub fn new(radius: u32, survival: RangeInclusive<u32>, birth: RangeInclusive<u32>) -> Self {
+        Self {
+            radius: radius.clamp(1, Self::MAX_RADIUS),
+            states: 2,
+            include_center: false,
+            survival: (*survival.start(), *survival.end()),
+            birth: (*birth.start(), *birth.end()),
+            neighborhood: Neighborhood::Moore,
+        }
+    }
+
+    /// Sets the number of states, at least 2.
+    pub fn with_states(self, states: u8) -> Self {
+        Self {
+            states: states.max(2),
+            ..self
+        }
+    }
+
+    pub fn with_center(self, include_center: bool) -> Self {
+        Self {
+            include_center,
+            ..self
+        }
+    }
+
+    pub fn with_neighborhood(self, neighborhood: Neighborhood) -> Self {
+        Self {
+            neighborhood,
+            ..self
+        }
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn include_center(&self) -> bool {
+        self.include_center
+    }
+
+    pub fn survival(&self) -> RangeInclusive<u32> {
+        self.survival.0..=self.survival.1
+    }
+
+    pub fn birth(&self) -> RangeInclusive<u32> {
+        self.birth.0..=self.birth.1
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// Returns the new state of a cell, given its current state and the number of alive neighbors.
+    pub fn next_state(&self, current_state: u8, alive_neighbors: u32) -> u8 {
+        match current_state {
+            0 => u8::from(self.birth().contains(&alive_neighbors)),
+            1 if self.survival().contains(&alive_neighbors) => 1,
+            dying => (dying + 1) % self.states,
+        }
+    }
+}
+
+impl FromStr for LtlRule {
+    type Err = ParseRuleError;
+
+    /// Parses a rule in the `R5,C0,M1,S34..58,B34..45,NM` notation. The radius and ranges are required,
+    /// `C0` (two states), `M0` and `NM` are the defaults. The neighborhood is `NM` for Moore,
+    /// `NN` for von Neumann or `NC` for circular.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRuleError::new(s, "R5,C0,M1,S34..58,B34..45,NM");
+        let parse_range = |range: &str| -> Result<(u32, u32), ParseRuleError> {
+            let (min, max) = range.split_once("..").ok_or_else(error)?;
+            let (min, max) = (
+                min.parse().map_err(|_| error())?,
+                max.parse().map_err(|_| error())?,
+            );
+            Ok((min, max))
+        };
+        let mut rule = Self::new(1, 0..=0, 0..=0);
+        let (mut radius, mut survival, mut birth) = (None, None, None);
+        for part in s.trim().split(',') {
+            let mut characters = part.chars();
+            let letter = characters.next().ok_or_else(error)?;
+            let value = characters.as_str();
+            match letter.to_ascii_uppercase() {
+                'R' => radius = Some(value.parse::<u32>().map_err(|_| error())?),
+                'C' => rule.states = value.parse::<u8>().map_err(|_| error())?.max(2),
+                'M' => {
+                    rule.include_center = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(error()),
+                    }
+                }
+                'S' => survival = Some(parse_range(value)?),
+                'B' => birth = Some(parse_range(value)?),
+                'N' => {
+                    rule.neighborhood = match value {
+                        "M" | "m" => Neighborhood::Moore,
+                        "N" | "n" => Neighborhood::VonNeumann,
+                        "C" | "c" => Neighborhood::Circular,
+                        _ => return Err(error()),
+                    }
+                }
+                _ => return Err(error()),
+            }
+        }
+        match (radius, survival, birth) {
+            (Some(radius @ 1..=Self::MAX_RADIUS), Some(survival), Some(birth)) => {
+                rule.radius = radius;
+                rule.survival = survival;
+                rule.birth = birth;
+                Ok(rule)
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for LtlRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "R{},C{},M{},S{}..{},B{}..{},N{}",
+            self.radius,
+            if self.states == 2 { 0 } else { self.states },
+            u8::from(self.include_center),
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1,
+            self.neighborhood.letter()
+        )
+    }
+}
+
+/// Numbers of alive cells in every rectangle of a grid extended by the radius on each side,
+/// so that a neighborhood is counted with a few lookups instead of a loop over its cells.
+struct SummedAreaTable {
+    /// `sums[i][j]` is the number of alive cells in the rows before `i` and the columns before `j`
+    /// of the extended grid.
+    sums: Vec<Vec<u32>>,
+}
+
+impl SummedAreaTable {
+    /// Extends the pool by `radius` cells on each side, dead for a bounded pool and wrapped for a torus.
+    fn new(pool: &LtlPool, radius: u32) -> Self {
+        let (height, width) = (pool.height() as i64, pool.width() as i64);
+        let radius = radius as i64;
+        let is_alive = |row: i64, column: i64| match pool.topology {
+            Topology::Bounded => {
+                (0..height).contains(&row)
+                    && (0..width).contains(&column)
+                    && pool.state[row as usize][column as usize] == 1
+            }
+            Topology::Torus => {
+                pool.state[row.rem_euclid(height) as usize][column.rem_euclid(width) as usize] == 1
+            }
+        };
+
+        let (extended_height, extended_width) = (height + 2 * radius, width + 2 * radius);
+        let mut sums = vec![vec![0; extended_width as usize + 1]; extended_height as usize + 1];
+        for i in 0..extended_height as usize {
+            let mut row_sum = 0;
+            for j in 0..extended_width as usize {
+                row_sum += u32::from(is_alive(i as i64 - radius, j as i64 - radius));
+                sums[i + 1][j + 1] = sums[i][j + 1] + row_sum;
+            }
+        }
+        Self { sums }
+    }
+
+    /// Returns the number of alive cells in the rows `top..bottom` and columns `left..right` of the extended grid.
+    fn count(&self, top: u32, left: u32, bottom: u32, right: u32) -> u32 {
+        let (top, left, bottom, right) =
+            (top as usize, left as usize, bottom as usize, right as usize);
+        self.sums[bottom][right] + self.sums[top][left]
+            - self.sums[top][right]
+            - self.sums[bottom][left]
+    }
+}
+
+/// A pool of cells following a Larger than Life rule, each cell storing its state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LtlPool {
+    /// State of each cell, indexed `[row][column]`, `1` being alive and `2..` dying.
+    state: Vec<Vec<u8>>,
+    /// Behavior of the edges.
+    topology: Topology,
+    rule: LtlRule,
+}
+
+impl LtlPool {
+    pub fn new(width: u32, height: u32, rule: LtlRule) -> Self {
+        Self {
+            state: vec![vec![0; width as usize]; height as usize],
+            topology: Topology::default(),
+            rule,
+        }
+    }
+
+    /// Creates a pool of the same size and topology, in which the alive cells of `pool` are alive.
+    pub fn from_pool(pool: &Pool, rule: LtlRule) -> Self {
+        let mut result = Self::new(pool.width(), pool.height(), rule);
+        result.topology = pool.topology();
+        for (row, cells) in result.state.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                *cell = u8::from(pool.get_cell(row as u32, column as u32));
+            }
+        }
+        result
+    }
+
+    pub fn width(&self) -> u32 {
+        self.state.first().map_or(0, |row| row.len() as u32)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.state.len() as u32
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn rule(&self) -> LtlRule {
+        self.rule
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> u8 {
+        self.state[row as usize][column as usize]
+    }
+
+    pub fn set_cell(&mut self, row: u32, column: u32, state: u8) {
+        self.state[row as usize][column as usize] = state;
+    }
+
+    /// Returns the number of alive cells, dying cells excluded.
+    pub fn population(&self) -> u32 {
+        self.state
+            .iter()
+            .flatten()
+            .filter(|cell| **cell == 1)
+            .count() as u32
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.state.iter_mut().flatten() {
+            *cell = 0;
+        }
+    }
+
+    /// Counts the alive neighbors of a cell, as given by the summed-area table of the pool.
+    fn count_alive_neighbors(
+        &self,
+        table: &SummedAreaTable,
+        half_widths: &[u32],
+        row: u32,
+        column: u32,
+    ) -> u32 {
+        let radius = self.rule.radius;
+        // Top left corner of the neighborhood in the extended grid, whose cell (radius, radius) is (0, 0).
+        let (top, left) = (row, column);
+        let mut count = match self.rule.neighborhood {
+            Neighborhood::Moore => {
+                table.count(top, left, top + 2 * radius + 1, left + 2 * radius + 1)
+            }
+            _ => half_widths
+                .iter()
+                .enumerate()
+                .map(|(offset, half_width)| {
+                    let extended_row = top + offset as u32;
+                    table.count(
+                        extended_row,
+                        left + radius - half_width,
+                        extended_row + 1,
+                        left + radius + half_width + 1,
+                    )
+                })
+                .sum(),
+        };
+        if !self.rule.include_center && self.get_cell(row, column) == 1 {
+            count -= 1;
+        }
+        count
+    }
+
+    /// Computes the next state of the rows from `start` included to `stop` excluded.
+    fn next_state_rows(
+        &self,
+        table: &SummedAreaTable,
+        half_widths: &[u32],
+        start: u32,
+        stop: u32,
+    ) -> Vec<Vec<u8>> {
+        (start..stop)
+            .map(|row| {
+                (0..self.width())
+                    .map(|column| {
+                        let count = self.count_alive_neighbors(table, half_widths, row, column);
+                        self.rule.next_state(self.get_cell(row, column), count)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Computes the next generation from a summed-area table of the pool,
+    /// on several threads like `Pool::step`.
+    pub fn step(&mut self) {
+        let radius = self.rule.radius;
+        let table = SummedAreaTable::new(self, radius);
+        // Half widths of the rows of the neighborhood, from the top one to the bottom one.
+        let half_widths: Vec<u32> = (0..=2 * radius)
+            .map(|row| {
+                self.rule
+                    .neighborhood
+                    .half_width(radius, row.abs_diff(radius))
+            })
+            .collect();
+        self.state = compute_next_state(self.height(), self.width(), |start, stop| {
+            self.next_state_rows(&table, &half_widths, start, stop)
+        });
+    }
+}
+
+impl StateGrid for LtlPool {
+    fn width(&self) -> u32 {
+        LtlPool::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        LtlPool::height(self)
+    }
+
+    fn state_count(&self) -> u8 {
+        self.rule.states()
+    }
+
+    fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.get_cell(row, column)
+    }
+
+    fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        self.set_cell(row, column, state)
+    }
+
+    fn step(&mut self) {
+        LtlPool::step(self)
+    }
+
+    fn clear(&mut self) {
+        LtlPool::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::RandomFill;
+
+    #[test]
+    fn parse() {
+        let bosco: LtlRule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+        assert_eq!(bosco, LtlRule::bosco());
+        assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!(
+            "r2,c3,s1..2,b3..3,nn".parse(),
+            Ok(LtlRule::new(2, 1..=2, 3..=3)
+                .with_states(3)
+                .with_neighborhood(Neighborhood::VonNeumann))
+        );
+        for invalid in [
+            "R5,S34..58",
+            "R0,S1..2,B3..3",
+            "R5,M2,S34..58,B34..45",
+            "R5,S34-58,B34..45",
+            "R5,S34..58,B34..45,NX",
+            "B3/S23",
+        ] {
+            assert!(invalid.parse::<LtlRule>().is_err(), "{} accepted", invalid);
+        }
+    }
+
+    #[test]
+    fn neighborhood_sizes() {
+        let sizes = |neighborhood: Neighborhood, radius: u32| -> u32 {
+            (0..=2 * radius)
+                .map(|row| 2 * neighborhood.half_width(radius, row.abs_diff(radius)) + 1)
+                .sum()
+        };
+        assert_eq!(sizes(Neighborhood::Moore, 5), 121);
+        assert_eq!(sizes(Neighborhood::VonNeumann, 2), 13);
+        assert_eq!(sizes(Neighborhood::Circular, 1), 9);
+        assert_eq!(sizes(Neighborhood::Circular, 2), 21);
+        assert_eq!(sizes(Neighborhood::Circular, 3), 37);
+        assert_eq!(sizes(Neighborhood::Circular, 5), 97);
+    }
+
+    /// Counts the alive neighbors of a cell by going through its neighborhood.
+    fn count_naively(pool: &LtlPool, row: u32, column: u32) -> u32 {
+        let (height, width, radius) = (
+            pool.height() as i64,
+            pool.width() as i64,
+            pool.rule.radius as i64,
+        );
+        let mut count = 0;
+        for i in -radius..=radius {
+            let half_width =
+                pool.rule
+                    .neighborhood
+                    .half_width(radius as u32, i.unsigned_abs() as u32) as i64;
+            for j in -half_width..=half_width {
+                if (i, j) == (0, 0) && !pool.rule.include_center {
+                    continue;
+                }
+                let (neighbor_row, neighbor_column) = (row as i64 + i, column as i64 + j);
+                let alive = match pool.topology {
+                    Topology::Bounded => {
+                        (0..height).contains(&neighbor_row)
+                            && (0..width).contains(&neighbor_column)
+                            && pool.get_cell(neighbor_row as u32, neighbor_column as u32) == 1
+                    }
+                    Topology::Torus => {
+                        pool.get_cell(
+                            neighbor_row.rem_euclid(height) as u32,
+                            neighbor_column.rem_euclid(width) as u32,
+                        ) == 1
+                    }
+                };
+                count += u32::from(alive);
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn summed_area_table_counts() {
+        let mut random = Pool::new(23, 17);
+        random.randomize_with(&RandomFill::new(7, 0.4));
+        for neighborhood in [
+            Neighborhood::Moore,
+            Neighborhood::VonNeumann,
+            Neighborhood::Circular,
+        ] {
+            for topology in [Topology::Bounded, Topology::Torus] {
+                for include_center in [false, true] {
+                    let rule = LtlRule::new(3, 0..=0, 0..=0)
+                        .with_center(include_center)
+                        .with_neighborhood(neighborhood);
+                    let mut pool = LtlPool::from_pool(&random, rule);
+                    pool.set_topology(topology);
+                    let table = SummedAreaTable::new(&pool, 3);
+                    let half_widths: Vec<u32> = (0..=6u32)
+                        .map(|row| neighborhood.half_width(3, row.abs_diff(3)))
+                        .collect();
+                    for row in 0..pool.height() {
+                        for column in 0..pool.width() {
+                            assert_eq!(
+                                pool.count_alive_neighbors(&table, &half_widths, row, column),
+                                count_naively(&pool, row, column),
+                                "{:?} {:?} at ({}, {})",
+                                rule,
+                                topology,
+                                row,
+                                column
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn radius_one_is_life() {
+        let mut pool = Pool::new(30, 30);
+        pool.set_topology(Topology::Torus);
+        pool.randomize_with(&RandomFill::new(3, 0.3));
+        let life: LtlRule = "R1,C0,M0,S2..3,B3..3,NM".parse().unwrap();
+        let mut ltl_pool = LtlPool::from_pool(&pool, life);
+        for _ in 0..20 {
+            pool.step();
+            ltl_pool.step();
+        }
+        assert_eq!(ltl_pool, LtlPool::from_pool(&pool, life));
+    }
+
+    #[test]
+    fn dying_states() {
+        let rule = LtlRule::new(1, 8..=8, 9..=9).with_states(4);
+        let mut pool = LtlPool::new(5, 5, rule);
+        pool.set_cell(2, 2, 1);
+        let states: Vec<u8> = (0..4)
+            .map(|_| {
+                pool.step();
+                pool.get_cell(2, 2)
+            })
+            .collect();
+        assert_eq!(states, [2, 3, 0, 0]);
+    }
+}
@@ -0,0 +1,433 @@
+use crate::grid::StateGrid;
+use crate::pool::{compute_next_state, Pool, RandomFill, Topology};
+use crate::rule::ParseRuleError;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::{fmt, str::FromStr};
+
+/// Parameters of Lenia, a continuous automaton : the state of each cell is a value between 0 and 1,
+/// which grows or shrinks depending on a weighted average of the cells within a radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LeniaRule {
+    /// Radius of the kernel, in cells.
+    radius: u32,
+    /// Average of the neighborhood for which cells grow the most.
+    mu: f64,
+    /// Width of the growth function around `mu`.
+    sigma: f64,
+    /// Number of steps for a full growth, the inverse of the time step.
+    steps: u32,
+}
+impl Default for LeniaRule {
+    /// The parameters of Orbium, the most common Lenia glider, `R=13;T=10;m=0.15;s=0.015`.
+    fn default() -> Self {
+        Self::new(13, 0.15, 0.015)
+    }
+}
+
+impl LeniaRule {
+    pub const MAX_RADIUS: u32 = 100;
+
+    /// `radius` is between 1 and `MAX_RADIUS`, `sigma` is positive.
+    pub fn new(radius: u32, mu: f64, sigma: f64) -> Self {
+        Self {
+            radius: radius.clamp(1, Self::MAX_RADIUS),
+            mu,
+            sigma: sigma.max(f64::EPSILON),
+            steps: 10,
+        }
+    }
+
+    /// Changes the number of steps of a full growth, `T` in the notation, at least 1.
+    pub fn with_steps(self, steps: u32) -> Self {
+        Self {
+            steps: steps.max(1),
+            ..self
+        }
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    pub fn mu(&self) -> f64 {
+        self.mu
+    }
+
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Returns the growth of a cell given the weighted average of its neighborhood,
+    /// from -1 far from `mu` to 1 at `mu`.
+    pub fn growth(&self, average: f64) -> f64 {
+        let distance = (average - self.mu) / self.sigma;
+        2.0 * (-distance * distance / 2.0).exp() - 1.0
+    }
+
+    /// Returns the `(row offset, column offset, weight)` of the cells of the kernel, a smooth ring
+    /// whose weights add up to 1. Cells farther than the radius and the center have no weight.
+    pub fn kernel(&self) -> Vec<(i32, i32, f64)> {
+        let radius = self.radius as i32;
+        let mut kernel: Vec<(i32, i32, f64)> = (-radius..=radius)
+            .flat_map(|i| (-radius..=radius).map(move |j| (i, j)))
+            .filter_map(|(i, j)| {
+                let distance = ((i * i + j * j) as f64).sqrt() / self.radius as f64;
+                (distance > 0.0 && distance < 1.0)
+                    .then(|| (i, j, (4.0 - 1.0 / (distance * (1.0 - distance))).exp()))
+            })
+            .collect();
+        let total: f64 = kernel.iter().map(|(_, _, weight)| weight).sum();
+        for (_, _, weight) in &mut kernel {
+            *weight /= total;
+        }
+        kernel
+    }
+}
+
+impl FromStr for LeniaRule {
+    type Err = ParseRuleError;
+
+    /// Parses a rule in the `R=13;T=10;m=0.15;s=0.015` notation, missing parameters being
+    /// those of `LeniaRule::default`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRuleError::new(s, "R=13;T=10;m=0.15;s=0.015");
+        let mut rule = Self::default();
+        for parameter in s.trim().split(';') {
+            let (name, value) = parameter.split_once('=').ok_or_else(error)?;
+            let value = value.trim();
+            match name.trim() {
+                "R" => rule.radius = value.parse().map_err(|_| error())?,
+                "T" => rule.steps = value.parse().map_err(|_| error())?,
+                "m" => rule.mu = value.parse().map_err(|_| error())?,
+                "s" => rule.sigma = value.parse().map_err(|_| error())?,
+                _ => return Err(error()),
+            }
+        }
+        let valid = (1..=Self::MAX_RADIUS).contains(&rule.radius)
+            && rule.steps >= 1
+            && rule.mu.is_finite()
+            && rule.sigma.is_finite()
+            && rule.sigma > 0.0;
+        valid.then_some(rule).ok_or_else(error)
+    }
+}
+
+impl fmt::Display for LeniaRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "R={};T={};m={};s={}",
+            self.radius, self.steps, self.mu, self.sigma
+        )
+    }
+}
+
+/// A pool of cells following Lenia, each cell storing a value between 0 and 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeniaPool {
+    /// Value of each cell, indexed `[row][column]`.
+    state: Vec<Vec<f64>>,
+    /// Behavior of the edges.
+    topology: Topology,
+    rule: LeniaRule,
+    /// Kernel of the rule, see `LeniaRule::kernel`.
+    kernel: Vec<(i32, i32, f64)>,
+}
+
+impl LeniaPool {
+    /// Number of levels of the values seen as a `StateGrid`, which are shown in the window.
+    pub const LEVELS: u8 = u8::MAX;
+
+    pub fn new(width: u32, height: u32, rule: LeniaRule) -> Self {
+        Self {
+            state: vec![vec![0.0; width as usize]; height as usize],
+            topology: Topology::default(),
+            rule,
+            kernel: rule.kernel(),
+        }
+    }
+
+    /// Creates a pool of the same size and topology, in which the alive cells of `pool` have the value 1.
+    pub fn from_pool(pool: &Pool, rule: LeniaRule) -> Self {
+        let mut result = Self::new(pool.width(), pool.height(), rule);
+        result.topology = pool.topology();
+        for (row, cells) in result.state.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                if pool.get_cell(row as u32, column as u32) {
+                    *cell = 1.0;
+                }
+            }
+        }
+        result
+    }
+
+    pub fn width(&self) -> u32 {
+        self.state.first().map_or(0, |row| row.len() as u32)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.state.len() as u32
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn rule(&self) -> LeniaRule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: LeniaRule) {
+        self.rule = rule;
+        self.kernel = rule.kernel();
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> f64 {
+        self.state[row as usize][column as usize]
+    }
+
+    /// The value is clamped between 0 and 1.
+    pub fn set_cell(&mut self, row: u32, column: u32, value: f64) {
+        self.state[row as usize][column as usize] = value.clamp(0.0, 1.0);
+    }
+
+    /// Returns the sum of the values of the cells.
+    pub fn mass(&self) -> f64 {
+        self.state.iter().flatten().sum()
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.state.iter_mut().flatten() {
+            *cell = 0.0;
+        }
+    }
+
+    /// Gives the cells filled as described by the given fill, see `Pool::randomize_with`,
+    /// random values between 0 and 1. The other cells of its area become 0.
+    pub fn randomize_with(&mut self, fill: &RandomFill) {
+        let area = fill.filled_area(self.height(), self.width());
+        let mut alive = vec![vec![false; self.width() as usize]; self.height() as usize];
+        fill.apply(&mut alive);
+        // The values come from another stream than the alive cells, so that they do not follow them.
+        let mut rng = ChaCha8Rng::seed_from_u64(fill.seed);
+        rng.set_stream(1);
+        for row in area.row..area.row + area.height {
+            for column in area.column..area.column + area.width {
+                self.state[row as usize][column as usize] = if alive[row as usize][column as usize]
+                {
+                    rng.gen()
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+
+    /// Returns the weighted average of the neighborhood of a cell.
+    fn average(&self, row: u32, column: u32) -> f64 {
+        let (height, width) = (self.height() as i64, self.width() as i64);
+        self.kernel
+            .iter()
+            .filter_map(|(i, j, weight)| {
+                let (mut i, mut j) = (row as i64 + *i as i64, column as i64 + *j as i64);
+                if self.topology == Topology::Torus {
+                    (i, j) = (i.rem_euclid(height), j.rem_euclid(width));
+                }
+                let value = self.state.get(usize::try_from(i).ok()?)?;
+                Some(value.get(usize::try_from(j).ok()?)? * weight)
+            })
+            .sum()
+    }
+
+    /// Computes the next state of the rows from `start` included to `stop` excluded.
+    fn next_state_rows(&self, start: u32, stop: u32) -> Vec<Vec<f64>> {
+        let time_step = 1.0 / self.rule.steps as f64;
+        (start..stop)
+            .map(|row| {
+                (0..self.width())
+                    .map(|column| {
+                        let growth = self.rule.growth(self.average(row, column));
+                        (self.get_cell(row, column) + time_step * growth).clamp(0.0, 1.0)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Computes the next generation, on several threads like `Pool::step`.
+    pub fn step(&mut self) {
+        self.state = compute_next_state(self.height(), self.width(), |start, stop| {
+            self.next_state_rows(start, stop)
+        });
+    }
+}
+
+impl StateGrid for LeniaPool {
+    fn width(&self) -> u32 {
+        LeniaPool::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        LeniaPool::height(self)
+    }
+
+    /// Values are rounded to `LEVELS` levels, from 0 for 0 to `LEVELS - 1` for 1.
+    fn state_count(&self) -> u8 {
+        Self::LEVELS
+    }
+
+    fn get_state(&self, row: u32, column: u32) -> u8 {
+        (self.get_cell(row, column) * (Self::LEVELS - 1) as f64).round() as u8
+    }
+
+    fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        self.set_cell(row, column, state as f64 / (Self::LEVELS - 1) as f64)
+    }
+
+    fn step(&mut self) {
+        LeniaPool::step(self)
+    }
+
+    fn clear(&mut self) {
+        LeniaPool::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Area;
+
+    #[test]
+    fn parse() {
+        let orbium = LeniaRule::default();
+        assert_eq!(orbium.to_string(), "R=13;T=10;m=0.15;s=0.015");
+        assert_eq!(orbium.to_string().parse(), Ok(orbium));
+        assert_eq!(
+            "R=10; m=0.2".parse(),
+            Ok(LeniaRule::new(10, 0.2, 0.015).with_steps(10))
+        );
+        for invalid in ["R=0", "R=13;x=1", "s=0", "T=0", "R13", "B3/S23"] {
+            assert!(invalid.parse::<LeniaRule>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn kernel_and_growth() {
+        let rule = LeniaRule::default();
+        let kernel = rule.kernel();
+        let total: f64 = kernel.iter().map(|(_, _, weight)| weight).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(kernel.iter().all(|(i, j, _)| i * i + j * j < 13 * 13));
+        assert!(!kernel.iter().any(|(i, j, _)| (*i, *j) == (0, 0)));
+        // The ring is the heaviest halfway to the radius.
+        let weight = |column: i32| {
+            kernel
+                .iter()
+                .find(|(i, j, _)| (*i, *j) == (0, column))
+                .map(|(_, _, weight)| *weight)
+                .unwrap()
+        };
+        assert!(weight(6) > weight(2) && weight(6) > weight(11));
+
+        assert_eq!(rule.growth(0.15), 1.0);
+        assert!(rule.growth(0.0) < -0.99 && rule.growth(0.5) < -0.99);
+        assert!(rule.growth(0.16) > 0.0 && rule.growth(0.17) < 0.0);
+    }
+
+    #[test]
+    fn step() {
+        let rule = LeniaRule::new(3, 0.15, 0.05);
+        let mut pool = LeniaPool::new(20, 20, rule);
+        pool.step();
+        assert_eq!(pool.mass(), 0.0);
+
+        pool.randomize_with(&RandomFill::new(5, 0.5));
+        let before = pool.clone();
+        pool.step();
+        // Each cell changes by its growth divided by the number of steps of a full growth.
+        for (row, column) in [(0, 0), (10, 7), (19, 19)] {
+            let growth = rule.growth(before.average(row, column));
+            let expected = (before.get_cell(row, column) + growth * 0.1).clamp(0.0, 1.0);
+            assert_eq!(pool.get_cell(row, column), expected);
+        }
+        assert!(pool
+            .state
+            .iter()
+            .flatten()
+            .all(|cell| (0.0..=1.0).contains(cell)));
+    }
+
+    #[test]
+    fn torus_is_translation_invariant() {
+        let mut pool = LeniaPool::new(130, 130, LeniaRule::new(5, 0.15, 0.03));
+        pool.set_topology(Topology::Torus);
+        pool.randomize_with(&RandomFill::new(8, 0.5));
+        let mut shifted = pool.clone();
+        for row in 0..130 {
+            for column in 0..130 {
+                shifted.set_cell(
+                    (row + 40) % 130,
+                    (column + 70) % 130,
+                    pool.get_cell(row, column),
+                );
+            }
+        }
+        pool.step();
+        shifted.step();
+        for row in 0..130 {
+            for column in 0..130 {
+                let difference = shifted.get_cell((row + 40) % 130, (column + 70) % 130)
+                    - pool.get_cell(row, column);
+                assert!(difference.abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn randomize_area() {
+        let mut pool = LeniaPool::new(12, 10, LeniaRule::default());
+        pool.state = vec![vec![0.5; 12]; 10];
+        let area = Area {
+            row: 2,
+            column: 3,
+            height: 5,
+            width: 6,
+        };
+        pool.randomize_with(&RandomFill::new(3, 0.5).with_area(area));
+        let mut alive = Pool::new(12, 10);
+        alive.randomize_with(&RandomFill::new(3, 0.5).with_area(area));
+        for row in 0..10 {
+            for column in 0..12 {
+                let value = pool.get_cell(row, column);
+                if !(2..7).contains(&row) || !(3..9).contains(&column) {
+                    assert_eq!(value, 0.5);
+                } else if alive.get_cell(row, column) {
+                    assert!(value > 0.0 && value < 1.0);
+                } else {
+                    assert_eq!(value, 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn levels() {
+        let mut pool = LeniaPool::new(2, 1, LeniaRule::default());
+        pool.set_state(0, 0, LeniaPool::LEVELS - 1);
+        assert_eq!(pool.get_cell(0, 0), 1.0);
+        pool.set_cell(0, 1, 0.5);
+        assert_eq!(pool.get_state(0, 1), 127);
+        pool.set_cell(0, 1, 2.0);
+        assert_eq!(pool.get_state(0, 1), LeniaPool::LEVELS - 1);
+    }
+}
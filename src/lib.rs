@@ -0,0 +1,50 @@
+//! John Conway's game of life, and the tools around it : rules, pattern files,
+//! statistics, history and soup search.
+//!
+//! The window and terminal frontends are part of the binary, behind the `gui` and `tui` features.
+//!
+//! Without the default `std` feature the crate is `no_std`. The `alloc` feature keeps the heap
+//! allocated [`Pool`], otherwise only the fixed size [`FixedPool`] is available.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod census;
+#[cfg(feature = "alloc")]
+pub mod elementary;
+pub mod fixed_pool;
+#[cfg(feature = "alloc")]
+pub mod generations;
+pub mod grid;
+#[cfg(feature = "alloc")]
+pub mod history;
+#[cfg(feature = "alloc")]
+pub mod larger_than_life;
+#[cfg(feature = "std")]
+pub mod lenia;
+#[cfg(feature = "alloc")]
+pub mod margolus;
+#[cfg(feature = "std")]
+pub mod pattern;
+pub mod pool;
+#[cfg(feature = "alloc")]
+pub mod rewind;
+pub mod rule;
+#[cfg(feature = "alloc")]
+pub mod rule_file;
+#[cfg(feature = "std")]
+pub mod soup;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "alloc")]
+pub mod wireworld;
+
+pub use fixed_pool::FixedPool;
+pub use grid::{FromSize, Grid, StateGrid};
+#[cfg(feature = "alloc")]
+pub use pool::Pool;
+pub use pool::{Area, RandomFill, Symmetry, Topology};
+pub use rule::Rule;
@@ -1,373 +1,186 @@
-mod pool;
-
-use graphics::types::{Color, Scalar};
-use opengl_graphics::OpenGL;
-use piston::input::{UpdateArgs, UpdateEvent};
-use piston::window::WindowSettings;
-use piston::{
-    Button, Event, EventLoop, Key, MouseButton, MouseCursorEvent, PressEvent, ReleaseEvent,
-};
-use piston_window::PistonWindow as Window;
-
-use pool::Pool;
-
-const WIDTH: usize = 128;
-const HEIGHT: usize = 72;
-const PIXEL_PER_CELL: usize = 10;
-
-#[derive(PartialEq, Eq)]
-enum SelectedPoolStructure {
-    None,
-    Glider,
-    Acorn,
-}
-impl Default for SelectedPoolStructure {
-    fn default() -> Self {
-        SelectedPoolStructure::None
-    }
-}
-
-pub struct App {
-    pool: Pool,
-    window: Window,
-    cursor: [f64; 2],
-    paused: bool,
-    mouse_button_pressed: Option<MouseButton>,
-    selected_pool_structure: SelectedPoolStructure,
-    percent_speed: u8,
-    render_help: bool,
-}
-impl Default for App {
-    fn default() -> Self {
-        App::new(128, 72)
+mod cli;
+#[cfg(feature = "gui")]
+mod gui;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "gui")]
+mod viewer;
+#[cfg(feature = "gui")]
+mod viewport;
+#[cfg(feature = "gui")]
+mod window;
+
+use clap::Parser as _;
+#[cfg(feature = "gui")]
+use game_of_life::lenia;
+use game_of_life::stats::Statistics;
+use game_of_life::{pattern, soup, Pool};
+use std::path::Path;
+
+/// Runs a headless soup search and writes its census.
+fn run_soup_search(seed: u64, soups: u64, output: &Path) {
+    let mut search = soup::SoupSearch::new(seed);
+    search.run(soups);
+    if let Err(error) = search.write_report(output) {
+        eprintln!("Cannot write {} : {}", output.display(), error);
+        std::process::exit(1);
     }
+    println!(
+        "{} soups searched, {} distinct objects, census written to {}",
+        search.soup_count(),
+        search.counts().len(),
+        output.display()
+    );
 }
 
-impl App {
-    const MAX_FPS: u64 = 165;
-    const SPEED_STEP: u64 = 10;
-
-    fn new(width: u32, height: u32) -> App {
-        let mut pool: Pool = Pool::new(width, height);
-        pool.randomize();
-        let window: Window = WindowSettings::new(
-            "Game of life",
-            [
-                (WIDTH * PIXEL_PER_CELL) as u32,
-                (HEIGHT * PIXEL_PER_CELL) as u32,
-            ],
-        )
-        .graphics_api(OpenGL::V3_2)
-        .exit_on_esc(true)
-        .resizable(false)
-        .build()
-        .unwrap();
-
-        Self {
-            pool: pool,
-            window: window,
-            cursor: Default::default(),
-            paused: false,
-            mouse_button_pressed: None,
-            selected_pool_structure: Default::default(),
-            percent_speed: 10,
-            render_help: true,
+/// Runs the pool for the given number of generations without a window,
+/// then writes its final state and the statistics of every generation.
+/// Exits with 1 if a file cannot be written.
+fn run_batch(
+    mut pool: Pool,
+    generations: u64,
+    output: Option<&Path>,
+    format: Option<pattern::Format>,
+    stats: Option<&Path>,
+) {
+    let mut statistics = stats.map(|_| Statistics::new(generations as usize));
+    for generation in 1..=generations {
+        let previous = statistics.as_ref().map(|_| pool.clone());
+        pool.step();
+        if let (Some(statistics), Some(previous)) = (&mut statistics, previous) {
+            statistics.record(generation, &previous, &pool);
         }
     }
 
-    fn get_cell_pixel_coordinates(row: u32, column: u32) -> (u32, u32) {
-        (row * PIXEL_PER_CELL as u32, column * PIXEL_PER_CELL as u32)
-    }
-
-    fn cursor_to_cell_coordinates(cursor: [f64; 2]) -> (u32, u32) {
-        (
-            (cursor[1] / PIXEL_PER_CELL as f64) as u32,
-            (cursor[0] / PIXEL_PER_CELL as f64) as u32,
-        )
-    }
-
-    fn get_selected_pool(&self) -> Pool {
-        match self.selected_pool_structure {
-            SelectedPoolStructure::None => Pool::from_array(&[[true]]),
-            SelectedPoolStructure::Glider => Pool::glider_south_east(),
-            SelectedPoolStructure::Acorn => Pool::acorn(),
+    let exit_on_error = |result: std::io::Result<()>, path: &Path| {
+        if let Err(error) = result {
+            eprintln!("Cannot write {} : {}", path.display(), error);
+            std::process::exit(1);
         }
+    };
+    let format =
+        format.unwrap_or_else(|| output.map_or(pattern::Format::Rle, pattern::Format::from_path));
+    match output {
+        Some(path) => exit_on_error(pattern::save(&pool, path, format), path),
+        None => print!("{}", pattern::to_string(&pool, format)),
     }
-
-    pub fn render(&mut self, event: &Event) {
-        use graphics::*;
-
-        const LIFE_COLOR: Color = [0.0, 0.0, 0.0, 1.0];
-        const DEAD_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
-        const HINT_COLOR: Color = [0.0, 0.0, 0.0, 0.5];
-        const TEXT_COLOR: Color = [0.9, 0.1, 0.1, 1.0];
-
-        let selected_pool = self.get_selected_pool();
-
-        // Load font for text
-        const ASSETS: &str = "assets/";
-        const FONT_NAME: &str = "FiraSans-Bold.ttf";
-        let mut glyphs = self
-            .window
-            .load_font(ASSETS.to_owned() + FONT_NAME)
-            .expect(&format!("Cannot load font {}", FONT_NAME));
-
-        self.window.draw_2d(event, |c, g, device| {
-            // Clear the screen.
-            clear(DEAD_COLOR, g);
-
-            // Draw a square for each living cell
-            for i in 0..HEIGHT {
-                for j in 0..WIDTH {
-                    if self.pool.get_cell(i as u32, j as u32) {
-                        let (i_px, j_px) = Self::get_cell_pixel_coordinates(i as u32, j as u32);
-                        rectangle(
-                            LIFE_COLOR,
-                            rectangle::square(0.0, 0.0, PIXEL_PER_CELL as f64),
-                            c.transform.trans(j_px as f64, i_px as f64),
-                            g,
-                        );
-                    }
-                }
-            }
-
-            // Draw pixels about to be drawn with transparency
-            let (selected_row, selected_column) = Self::cursor_to_cell_coordinates(self.cursor);
-            for i in 0..selected_pool.height() {
-                for j in 0..selected_pool.width() {
-                    if selected_pool.get_cell(i, j) {
-                        let hint_row = i + selected_row;
-                        let hint_column = j + selected_column;
-                        let (hint_row_px, hint_column_px) =
-                            Self::get_cell_pixel_coordinates(hint_row, hint_column);
-                        rectangle(
-                            HINT_COLOR,
-                            rectangle::square(0.0, 0.0, PIXEL_PER_CELL as f64),
-                            c.transform.trans(hint_column_px as f64, hint_row_px as f64),
-                            g,
-                        );
-                    }
-                }
-            }
-
-            if self.render_help {
-                const TEXT_HORIZONTAL_OFFSET: Scalar = 10.0;
-                const TEXT_VERTICAL_OFFSET: Scalar = 20.0;
-                const TEXT_FONT_SIZE: u32 = 16;
-                let mut vertical_position = TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &format!("← → : Speed : {}%", self.percent_speed),
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                vertical_position += TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &"H : toggle help",
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                vertical_position += TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &"Space : pause",
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                vertical_position += TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &"Left click : set cell",
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                vertical_position += TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &"Right click : kill cell",
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                vertical_position += TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &"del : clear screen",
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                vertical_position += TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &"R : randomize",
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                vertical_position += TEXT_VERTICAL_OFFSET;
-                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
-                    .draw(
-                        &"1-2 : select structure",
-                        &mut glyphs,
-                        &DrawState::default(),
-                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
-                        g,
-                    )
-                    .unwrap();
-                glyphs.factory.encoder.flush(device);
-            }
-        });
+    if let (Some(statistics), Some(path)) = (&statistics, stats) {
+        exit_on_error(statistics.save_csv(path), path);
     }
+    eprintln!(
+        "{} generations run, population {}",
+        generations,
+        pool.population()
+    );
+}
 
-    pub fn update(&mut self, _args: &UpdateArgs) {
-        if !self.paused {
-            self.pool.step();
-        }
-    }
+/// Opens a window showing the scene, paused and at the speed given on the command line.
+#[cfg(feature = "gui")]
+fn show<S: window::Scene>(mut scene: S, title: &str, args: &cli::Args) {
+    let mut frame = window::Frame::new(title, &scene);
+    frame.set_paused(args.paused);
+    frame.set_percent_speed(args.speed);
+    frame.run(&mut scene);
+}
 
-    fn process_mouse_press(&mut self, button: MouseButton) {
-        // Prevent press when already pressed
-        if self.mouse_button_pressed != None {
+fn main() {
+    let args = cli::Args::parse();
+    match &args.command {
+        Some(cli::Command::SoupSearch {
+            seed,
+            soups,
+            output,
+        }) => {
+            run_soup_search(*seed, *soups, output);
             return;
         }
-        self.mouse_button_pressed = Some(button)
-    }
-
-    fn process_mouse_release(&mut self, button: MouseButton) {
-        // Prevent release when no button currently pressed
-        if let Some(button_already_pressed) = self.mouse_button_pressed {
-            if button != button_already_pressed {
-                return;
-            }
-        }
-        self.mouse_button_pressed = None;
-    }
-
-    /// Sets or kills cells depending on the button currently pressed on the mouse.
-    fn handle_pressed_mouse(&mut self) {
-        if let Some(pressed_button) = self.mouse_button_pressed {
-            let (row, column) = Self::cursor_to_cell_coordinates(self.cursor);
-            match pressed_button {
-                MouseButton::Left => {
-                    let struct_to_add = self.get_selected_pool();
-                    let (row_offset, column_offset) = Self::cursor_to_cell_coordinates(self.cursor);
-                    self.pool += struct_to_add.with_offset(row_offset, column_offset)
-                }
-                MouseButton::Right => self.pool.set_cell(row, column, false),
-                _ => {}
-            }
-        }
-    }
-
-    /// Select given pool structure to be drawn if not already selected.
-    /// If already selected deselects it.
-    fn select_or_deselect_pool(&mut self, selected_pool: SelectedPoolStructure) {
-        self.selected_pool_structure = if self.selected_pool_structure == selected_pool {
-            SelectedPoolStructure::None
-        } else {
-            selected_pool
+        Some(cli::Command::Run {
+            generations,
+            output,
+            format,
+            stats,
+        }) => {
+            args.require_life_rule("run");
+            let (pool, _) = args.initial_pool();
+            run_batch(
+                pool,
+                *generations,
+                output.as_deref(),
+                *format,
+                stats.as_deref(),
+            );
+            return;
         }
-    }
-
-    fn process_keyboard(&mut self, key: Key) {
-        match key {
-            // Space : Pause / Resume when space is pressed
-            Key::Space => self.paused = !self.paused,
-            // Del : Clear pool
-            Key::Delete => self.pool.clear(),
-            // R : Randomize pool
-            Key::R => self.pool.randomize(),
-            // T : toggle help
-            Key::H => self.render_help = !self.render_help,
-            // Right / Left : modify speed
-            Key::Left => {
-                // Weird logic to set 1 instead of zero.
-                if self.percent_speed == 10 {
-                    self.percent_speed = 1;
-                } else if self.percent_speed > 10 {
-                    self.percent_speed -= Self::SPEED_STEP as u8;
-                }
-
-                let new_update_per_second = Self::MAX_FPS * self.percent_speed as u64 / 100;
-                self.window.set_ups(new_update_per_second);
+        #[cfg(feature = "tui")]
+        Some(cli::Command::Tui { glyphs }) => {
+            args.require_life_rule("tui");
+            let (pool, random_fill) = args.initial_pool();
+            let mut tui = tui::Tui::new(pool, random_fill, *glyphs);
+            tui.set_paused(args.paused);
+            tui.set_percent_speed(args.speed);
+            if let Err(error) = tui.run() {
+                eprintln!("Terminal error : {}", error);
+                std::process::exit(1);
             }
-            Key::Right => {
-                if self.percent_speed == 1 {
-                    self.percent_speed = 10;
-                } else if self.percent_speed < 100 {
-                    self.percent_speed += Self::SPEED_STEP as u8;
-                }
-                let new_update_per_second = Self::MAX_FPS * self.percent_speed as u64 / 100;
-                self.window.set_ups(new_update_per_second);
-            }
-            // 1 : select glider
-            Key::NumPad1 => self.select_or_deselect_pool(SelectedPoolStructure::Glider),
-            // 2 : select acorn
-            Key::NumPad2 => self.select_or_deselect_pool(SelectedPoolStructure::Acorn),
-
-            // TODO : add a rotated field for the selected pool structure, to be able to put glider in other directions
-
-            // Discard other keys
-            _ => {}
+            return;
         }
+        None => {}
     }
 
-    pub fn run(&mut self) {
-        let update_per_second = Self::MAX_FPS * self.percent_speed as u64 / 100;
-        self.window.set_max_fps(Self::MAX_FPS);
-        self.window.set_ups(update_per_second);
-        self.window.set_lazy(false);
-
-        while let Some(e) = self.window.next() {
-            // First capture mouse position.
-            e.mouse_cursor(|pos| {
-                self.cursor = pos.clone();
+    // Create a new game and run it.
+    #[cfg(feature = "gui")]
+    if let Some(pool) = args.initial_rule_file_pool() {
+        let palette = viewer::rule_file_palette(pool.rule());
+        let title = format!("Game of life - {}", pool.rule().name());
+        show(viewer::Viewer::new(pool, palette), &title, &args);
+    } else if let Some(automaton) = args.initial_elementary() {
+        let title = format!("Elementary automaton - {}", automaton.rule());
+        let viewer = viewer::Viewer::new(automaton, viewer::decay_palette(2))
+            .with_export("spacetime.rle", |automaton| {
+                pattern::to_rle(&automaton.to_pool())
             });
-            // Then process inputs.
-            if let Some(Button::Mouse(button)) = e.press_args() {
-                self.process_mouse_press(button);
-            }
-            if let Some(Button::Mouse(button)) = e.release_args() {
-                self.process_mouse_release(button);
-            }
-            self.handle_pressed_mouse();
-            if let Some(Button::Keyboard(key)) = e.press_args() {
-                self.process_keyboard(key);
-            };
-            // Update state accordingly.
-            if let Some(args) = e.update_args() {
-                self.update(&args);
-            }
-            // Finally render.
-
-            self.render(&e);
-        }
+        show(viewer, &title, &args);
+    } else if let Some(pool) = args.initial_margolus_pool() {
+        let title = format!("Margolus - {}", pool.rule());
+        show(
+            viewer::Viewer::new(pool, viewer::decay_palette(2)),
+            &title,
+            &args,
+        );
+    } else if let Some(pool) = args.initial_lenia_pool() {
+        let title = format!("Lenia - {}", pool.rule());
+        let levels = lenia::LeniaPool::LEVELS;
+        let viewer = viewer::Viewer::new(pool, viewer::colormap_palette(levels))
+            .with_alternative_palette(viewer::grayscale_palette(levels));
+        show(viewer, &title, &args);
+    } else if let Some(pool) = args.initial_generations_pool() {
+        let palette = viewer::decay_palette(pool.rule().states());
+        let title = format!("Game of life - {}", pool.rule());
+        show(viewer::Viewer::new(pool, palette), &title, &args);
+    } else if let Some(pool) = args.initial_ltl_pool() {
+        let palette = viewer::decay_palette(pool.rule().states());
+        let title = format!("Larger than Life - {}", pool.rule());
+        show(viewer::Viewer::new(pool, palette), &title, &args);
+    } else if let Some(circuit) = args.initial_wireworld() {
+        let palette = viewer::WIREWORLD_PALETTE.to_vec();
+        let viewer = viewer::Viewer::new(circuit, palette).with_state_names(&[
+            "Empty",
+            "Conductor",
+            "Electron head",
+            "Electron tail",
+        ]);
+        show(viewer, "WireWorld", &args);
+    } else {
+        let (pool, random_fill) = args.initial_pool();
+        show(
+            gui::App::with_pool(pool, random_fill),
+            "Game of life",
+            &args,
+        );
+    }
+    #[cfg(not(feature = "gui"))]
+    {
+        eprintln!("Built without the gui feature, use a command, see --help");
+        std::process::exit(2);
     }
-}
-
-fn main() {
-    // Create a new game and run it.
-    let mut app: App = Default::default();
-
-    app.run();
 }
@@ -0,0 +1,356 @@
+use crate::grid::StateGrid;
+use crate::pool::{Pool, Topology};
+use crate::rule::ParseRuleError;
+use alloc::{vec, vec::Vec};
+use core::{fmt, str::FromStr};
+
+/// A block rule of the Margolus neighborhood, giving the new state of each 2x2 block.
+///
+/// A block is numbered by its alive cells, the top left cell counting for 1, the top right one
+/// for 2, the bottom left one for 4 and the bottom right one for 8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MargolusRule {
+    /// New block of each block.
+    table: [u8; 16],
+}
+
+impl MargolusRule {
+    /// Every entry of the table must be below 16.
+    pub fn new(table: [u8; 16]) -> Self {
+        Self {
+            table: table.map(|block| block & 0b1111),
+        }
+    }
+
+    /// The billiard ball machine : single cells move diagonally away from the block,
+    /// two cells on a diagonal collide and leave on the other diagonal.
+    pub fn billiard_ball_machine() -> Self {
+        Self::new([0, 8, 4, 3, 2, 5, 9, 7, 1, 6, 10, 11, 12, 13, 14, 15])
+    }
+
+    /// Critters : blocks without exactly two alive cells are inverted,
+    /// and turned half a turn if they had three alive cells.
+    pub fn critters() -> Self {
+        Self::new(core::array::from_fn(|block| {
+            let block = block as u8;
+            let inverted = !block & 0b1111;
+            match block.count_ones() {
+                2 => block,
+                3 => rotate_half_turn(inverted),
+                _ => inverted,
+            }
+        }))
+    }
+
+    /// Returns the new state of a block.
+    pub fn next_block(&self, block: u8) -> u8 {
+        self.table[(block & 0b1111) as usize]
+    }
+
+    /// Returns the rule undoing this one, `None` if two blocks have the same new block.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut table = [None; 16];
+        for (block, next) in self.table.iter().enumerate() {
+            if table[*next as usize].replace(block as u8).is_some() {
+                return None;
+            }
+        }
+        Some(Self::new(table.map(|block| block.unwrap_or_default())))
+    }
+
+    /// Whether the rule can be run backward, see `inverse`.
+    pub fn is_reversible(&self) -> bool {
+        self.inverse().is_some()
+    }
+}
+
+/// Returns a block turned half a turn, exchanging opposite corners.
+fn rotate_half_turn(block: u8) -> u8 {
+    (0..4).fold(0, |rotated, bit| rotated | (block >> bit & 1) << (3 - bit))
+}
+
+impl FromStr for MargolusRule {
+    type Err = ParseRuleError;
+
+    /// Parses a rule in the `M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15` notation,
+    /// the new state of each of the 16 blocks.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRuleError::new(s, "M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15");
+        let blocks: Vec<u8> = s
+            .trim()
+            .strip_prefix(['M', 'm'])
+            .ok_or_else(error)?
+            .split(',')
+            .map(|block| block.trim().parse().ok().filter(|block| *block < 16))
+            .collect::<Option<_>>()
+            .ok_or_else(error)?;
+        Ok(Self::new(blocks.try_into().map_err(|_| error())?))
+    }
+}
+
+impl fmt::Display for MargolusRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "M")?;
+        for (index, block) in self.table.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", block)?;
+        }
+        Ok(())
+    }
+}
+
+/// A pool of cells following a Margolus block rule.
+///
+/// Generations alternate between two partitions into 2x2 blocks, the second one being offset by
+/// one cell down and right. On a torus, blocks wrap around the edges if the size is even, otherwise,
+/// like with bounded edges, cells outside of the complete blocks do not change.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MargolusPool {
+    /// State of each cell, indexed `[row][column]`.
+    state: Vec<Vec<bool>>,
+    /// Behavior of the edges.
+    topology: Topology,
+    rule: MargolusRule,
+    /// Whether the next step uses the offset partition.
+    odd_phase: bool,
+}
+
+impl MargolusPool {
+    pub fn new(width: u32, height: u32, rule: MargolusRule) -> Self {
+        Self {
+            state: vec![vec![false; width as usize]; height as usize],
+            topology: Topology::default(),
+            rule,
+            odd_phase: false,
+        }
+    }
+
+    /// Creates a pool of the same size, topology and alive cells as `pool`.
+    pub fn from_pool(pool: &Pool, rule: MargolusRule) -> Self {
+        let mut result = Self::new(pool.width(), pool.height(), rule);
+        result.topology = pool.topology();
+        for (row, cells) in result.state.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                *cell = pool.get_cell(row as u32, column as u32);
+            }
+        }
+        result
+    }
+
+    pub fn width(&self) -> u32 {
+        self.state.first().map_or(0, |row| row.len() as u32)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.state.len() as u32
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn rule(&self) -> MargolusRule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: MargolusRule) {
+        self.rule = rule;
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> bool {
+        self.state[row as usize][column as usize]
+    }
+
+    pub fn set_cell(&mut self, row: u32, column: u32, state: bool) {
+        self.state[row as usize][column as usize] = state;
+    }
+
+    pub fn population(&self) -> u32 {
+        self.state.iter().flatten().filter(|cell| **cell).count() as u32
+    }
+
+    /// Kills every cell and goes back to the first partition.
+    pub fn clear(&mut self) {
+        for cell in self.state.iter_mut().flatten() {
+            *cell = false;
+        }
+        self.odd_phase = false;
+    }
+
+    /// Returns the pairs of rows or columns of the blocks starting at `offset`, along a side of `size` cells.
+    fn block_pairs(&self, offset: u32, size: u32) -> impl Iterator<Item = (usize, usize)> {
+        let wraps = self.topology == Topology::Torus && size.is_multiple_of(2);
+        (offset..size).step_by(2).filter_map(move |start| {
+            let next = if start + 1 < size {
+                start + 1
+            } else if wraps {
+                0
+            } else {
+                return None;
+            };
+            Some((start as usize, next as usize))
+        })
+    }
+
+    /// Replaces every block of the current partition as given by the rule.
+    fn apply(&mut self, rule: MargolusRule, offset: u32) {
+        let rows: Vec<_> = self.block_pairs(offset, self.height()).collect();
+        let columns: Vec<_> = self.block_pairs(offset, self.width()).collect();
+        for (top, bottom) in &rows {
+            for (left, right) in &columns {
+                let cells = [
+                    (*top, *left),
+                    (*top, *right),
+                    (*bottom, *left),
+                    (*bottom, *right),
+                ];
+                let block = cells.iter().enumerate().fold(0, |block, (bit, (i, j))| {
+                    block | u8::from(self.state[*i][*j]) << bit
+                });
+                let next = rule.next_block(block);
+                for (bit, (i, j)) in cells.iter().enumerate() {
+                    self.state[*i][*j] = next >> bit & 1 != 0;
+                }
+            }
+        }
+    }
+
+    /// Computes the next generation, then switches to the other partition.
+    pub fn step(&mut self) {
+        self.apply(self.rule, u32::from(self.odd_phase));
+        self.odd_phase = !self.odd_phase;
+    }
+
+    /// Computes the previous generation, undoing `step`.
+    /// Returns `false` without changing anything if the rule is not reversible.
+    pub fn step_back(&mut self) -> bool {
+        let Some(inverse) = self.rule.inverse() else {
+            return false;
+        };
+        self.odd_phase = !self.odd_phase;
+        self.apply(inverse, u32::from(self.odd_phase));
+        true
+    }
+}
+
+impl StateGrid for MargolusPool {
+    fn width(&self) -> u32 {
+        MargolusPool::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        MargolusPool::height(self)
+    }
+
+    fn state_count(&self) -> u8 {
+        2
+    }
+
+    fn get_state(&self, row: u32, column: u32) -> u8 {
+        u8::from(self.get_cell(row, column))
+    }
+
+    fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        self.set_cell(row, column, state != 0)
+    }
+
+    fn step(&mut self) {
+        MargolusPool::step(self)
+    }
+
+    fn clear(&mut self) {
+        MargolusPool::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::RandomFill;
+
+    #[test]
+    fn parse() {
+        let billiard_ball_machine = MargolusRule::billiard_ball_machine();
+        assert_eq!(
+            billiard_ball_machine.to_string(),
+            "M0,8,4,3,2,5,9,7,1,6,10,11,12,13,14,15"
+        );
+        assert_eq!(
+            billiard_ball_machine.to_string().parse(),
+            Ok(billiard_ball_machine)
+        );
+        let mut table: [u8; 16] = core::array::from_fn(|block| block as u8);
+        table.swap(0, 15);
+        assert_eq!(
+            "m 15,1,2,3,4,5,6,7,8,9,10,11,12,13,14,0".parse(),
+            Ok(MargolusRule::new(table))
+        );
+        for invalid in ["M0,1,2", "M0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,16", "B3/S23"] {
+            assert!(invalid.parse::<MargolusRule>().is_err());
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        let critters = MargolusRule::critters();
+        assert_eq!(critters.next_block(0), 15);
+        assert_eq!(critters.next_block(0b0011), 0b0011);
+        // Three alive cells leave one, in the opposite corner of the dead one.
+        assert_eq!(critters.next_block(0b0111), 0b0001);
+        let inverse = critters.inverse().unwrap();
+        for block in 0..16 {
+            assert_eq!(inverse.next_block(critters.next_block(block)), block);
+        }
+        let billiard_ball_machine = MargolusRule::billiard_ball_machine();
+        assert_eq!(billiard_ball_machine.inverse(), Some(billiard_ball_machine));
+        assert!(!MargolusRule::new([0; 16]).is_reversible());
+    }
+
+    #[test]
+    fn billiard_ball() {
+        let mut pool = MargolusPool::new(8, 8, MargolusRule::billiard_ball_machine());
+        pool.set_cell(1, 1, true);
+        pool.step();
+        // The ball moves to the opposite corner of its block, then on in the offset block.
+        assert!(pool.get_cell(0, 0));
+        pool.clear();
+        pool.set_cell(2, 2, true);
+        for generation in 1..=4 {
+            pool.step();
+            assert_eq!(pool.population(), 1);
+            assert!(pool.get_cell(2 + generation, 2 + generation));
+        }
+    }
+
+    #[test]
+    fn reversibility() {
+        for rule in [
+            MargolusRule::critters(),
+            MargolusRule::billiard_ball_machine(),
+        ] {
+            for topology in [Topology::Bounded, Topology::Torus] {
+                let mut soup = Pool::new(30, 20);
+                soup.randomize_with(&RandomFill::new(7, 0.3));
+                let mut pool = MargolusPool::from_pool(&soup, rule);
+                pool.set_topology(topology);
+                let initial = pool.clone();
+                for _ in 0..100 {
+                    pool.step();
+                }
+                assert_ne!(pool, initial);
+                for _ in 0..100 {
+                    assert!(pool.step_back());
+                }
+                assert_eq!(pool, initial);
+            }
+        }
+        let mut pool = MargolusPool::new(4, 4, MargolusRule::new([0; 16]));
+        assert!(!pool.step_back());
+    }
+}
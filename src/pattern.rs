@@ -0,0 +1,411 @@
+use crate::grid::{FromSize, Grid};
+use crate::rule::{ParseRuleError, Rule};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::str::FromStr;
+use std::{error, fmt, fs, io};
+
+/// File formats of patterns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Run length encoded, `.rle` files.
+    Rle,
+    /// One character per cell, `O` for alive and `.` for dead, `.cells` files.
+    Plaintext,
+}
+impl Format {
+    /// Guesses the format from the extension of a file, defaults to RLE.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some(extension)
+                if extension.eq_ignore_ascii_case("cells")
+                    || extension.eq_ignore_ascii_case("txt") =>
+            {
+                Format::Plaintext
+            }
+            _ => Format::Rle,
+        }
+    }
+}
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rle" => Ok(Format::Rle),
+            "plaintext" | "cells" => Ok(Format::Plaintext),
+            _ => Err(format!(
+                "invalid format \"{}\", expected rle or plaintext",
+                s
+            )),
+        }
+    }
+}
+
+/// Error returned when a pattern cannot be read.
+#[derive(Debug)]
+pub enum PatternError {
+    Io(io::Error),
+    /// The RLE header line `x = ..., y = ...` is missing or malformed.
+    InvalidHeader(String),
+    InvalidRule(ParseRuleError),
+    /// A character which is not part of the format, with its line number starting at 1.
+    InvalidCharacter(char, usize),
+    /// The cells extend beyond the size given in the header.
+    OutOfBounds,
+    /// The grid read into cannot hold a pattern of this width and height,
+    /// or the pattern has more than `MAX_PATTERN_CELLS` cells.
+    TooLarge(u32, u32),
+}
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Io(error) => write!(f, "{}", error),
+            PatternError::InvalidHeader(header) => write!(f, "invalid RLE header \"{}\"", header),
+            PatternError::InvalidRule(error) => write!(f, "{}", error),
+            PatternError::InvalidCharacter(character, line) => {
+                write!(f, "unexpected character '{}' on line {}", character, line)
+            }
+            PatternError::OutOfBounds => write!(f, "cells outside of the declared pattern size"),
+            PatternError::TooLarge(width, height) => {
+                write!(f, "the {}x{} pattern is too large", width, height)
+            }
+        }
+    }
+}
+impl error::Error for PatternError {}
+impl From<io::Error> for PatternError {
+    fn from(error: io::Error) -> Self {
+        PatternError::Io(error)
+    }
+}
+impl From<ParseRuleError> for PatternError {
+    fn from(error: ParseRuleError) -> Self {
+        PatternError::InvalidRule(error)
+    }
+}
+
+/// Reads a pattern file, in the format given by its extension.
+pub fn load<G: FromSize, P: AsRef<Path>>(path: P) -> Result<G, PatternError> {
+    let text = fs::read_to_string(&path)?;
+    match Format::from_path(&path) {
+        Format::Rle => parse_rle(&text),
+        Format::Plaintext => parse_plaintext(&text),
+    }
+}
+
+/// Writes a pattern file in the given format.
+pub fn save<G: Grid, P: AsRef<Path>>(pool: &G, path: P, format: Format) -> io::Result<()> {
+    fs::write(path, to_string(pool, format))
+}
+
+pub fn to_string<G: Grid>(pool: &G, format: Format) -> String {
+    match format {
+        Format::Rle => to_rle(pool),
+        Format::Plaintext => to_plaintext(pool),
+    }
+}
+
+/// Encodes the whole pool, with its size and rule in the header.
+pub fn to_rle<G: Grid>(pool: &G) -> String {
+    const MAX_LINE_LENGTH: usize = 70;
+
+    // Runs of identical tags, dead cells at the end of rows and empty rows
+    // at the end of the pattern are omitted.
+    let mut runs: Vec<(u32, char)> = Vec::new();
+    let push = |runs: &mut Vec<(u32, char)>, count: u32, tag: char| {
+        if count == 0 {
+            return;
+        }
+        match runs.last_mut() {
+            Some((last_count, last_tag)) if *last_tag == tag => *last_count += count,
+            _ => runs.push((count, tag)),
+        }
+    };
+    let mut pending_rows = 0;
+    for row in 0..pool.height() {
+        let mut column = 0;
+        while column < pool.width() {
+            let alive = pool.get_cell(row, column);
+            let start = column;
+            while column < pool.width() && pool.get_cell(row, column) == alive {
+                column += 1;
+            }
+            if alive {
+                push(&mut runs, pending_rows, '$');
+                pending_rows = 0;
+                push(&mut runs, column - start, 'o');
+            } else if column < pool.width() {
+                push(&mut runs, pending_rows, '$');
+                pending_rows = 0;
+                push(&mut runs, column - start, 'b');
+            }
+        }
+        pending_rows += 1;
+    }
+
+    let mut text = format!(
+        "x = {}, y = {}, rule = {}\n",
+        pool.width(),
+        pool.height(),
+        pool.rule()
+    );
+    let mut line_length = 0;
+    for item in runs
+        .iter()
+        .map(|&(count, tag)| match count {
+            1 => tag.to_string(),
+            _ => format!("{}{}", count, tag),
+        })
+        .chain(std::iter::once(String::from("!")))
+    {
+        if line_length + item.len() > MAX_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        }
+        line_length += item.len();
+        text.push_str(&item);
+    }
+    text.push('\n');
+    text
+}
+
+/// Encodes the whole pool, one line per row. The rule is not part of the format.
+pub fn to_plaintext<G: Grid>(pool: &G) -> String {
+    let mut text = String::new();
+    for row in 0..pool.height() {
+        for column in 0..pool.width() {
+            text.push(if pool.get_cell(row, column) { 'O' } else { '.' });
+        }
+        writeln!(text).unwrap();
+    }
+    text
+}
+
+/// Largest number of cells of a pattern read from a file, so that a header cannot allocate without limit.
+pub const MAX_PATTERN_CELLS: u64 = 1 << 26;
+
+/// Parses a run length encoded pattern, the rule of the grid is the one of the header if any.
+pub fn parse_rle<G: FromSize>(text: &str) -> Result<G, PatternError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_start().starts_with('#') && !line.trim().is_empty());
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| PatternError::InvalidHeader(String::new()))?;
+    let invalid_header = || PatternError::InvalidHeader(header.to_owned());
+
+    let mut width = None;
+    let mut height = None;
+    let mut rule = Rule::default();
+    for field in header.split(',') {
+        let (key, value) = field.split_once('=').ok_or_else(invalid_header)?;
+        match key.trim() {
+            "x" => width = Some(value.trim().parse().map_err(|_| invalid_header())?),
+            "y" => height = Some(value.trim().parse().map_err(|_| invalid_header())?),
+            "rule" => rule = value.trim().parse()?,
+            _ => return Err(invalid_header()),
+        }
+    }
+    let (width, height): (u32, u32) = width.zip(height).ok_or_else(invalid_header)?;
+    if width as u64 * height as u64 > MAX_PATTERN_CELLS {
+        return Err(invalid_header());
+    }
+
+    let mut pool =
+        G::from_size(width, height, rule).ok_or(PatternError::TooLarge(width, height))?;
+    let (mut row, mut column) = (0u32, 0u32);
+    let mut run_count = 0u32;
+    'lines: for (line_index, line) in lines {
+        for character in line.chars() {
+            let run = run_count.max(1);
+            match character {
+                '0'..='9' => {
+                    // No run fits in a pattern once it overflows.
+                    run_count = run_count
+                        .checked_mul(10)
+                        .and_then(|count| count.checked_add(character.to_digit(10).unwrap()))
+                        .ok_or(PatternError::OutOfBounds)?;
+                    continue;
+                }
+                'b' | '.' => column = column.checked_add(run).ok_or(PatternError::OutOfBounds)?,
+                'o' => {
+                    if row >= height || column.checked_add(run).is_none_or(|end| end > width) {
+                        return Err(PatternError::OutOfBounds);
+                    }
+                    for _ in 0..run {
+                        pool.set_cell(row, column, true);
+                        column += 1;
+                    }
+                }
+                '$' => {
+                    row = row.checked_add(run).ok_or(PatternError::OutOfBounds)?;
+                    column = 0;
+                }
+                '!' => break 'lines,
+                c if c.is_whitespace() => continue,
+                c => return Err(PatternError::InvalidCharacter(c, line_index + 1)),
+            }
+            run_count = 0;
+        }
+    }
+    Ok(pool)
+}
+
+/// Parses a plaintext pattern, lines starting with `!` being comments.
+pub fn parse_plaintext<G: FromSize>(text: &str) -> Result<G, PatternError> {
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.starts_with('!'))
+        .map(|(index, line)| (index, line.trim_end()))
+        .collect();
+    let width = lines
+        .iter()
+        .map(|(_, line)| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    if width as u64 * lines.len() as u64 > MAX_PATTERN_CELLS {
+        return Err(PatternError::TooLarge(
+            width.try_into().unwrap_or(u32::MAX),
+            lines.len().try_into().unwrap_or(u32::MAX),
+        ));
+    }
+
+    let (width, height) = (width as u32, lines.len() as u32);
+    let mut pool = G::from_size(width, height, Default::default())
+        .ok_or(PatternError::TooLarge(width, height))?;
+    for (row, (line_index, line)) in lines.iter().enumerate() {
+        for (column, character) in line.chars().enumerate() {
+            match character {
+                'O' | 'o' | '*' => pool.set_cell(row as u32, column as u32, true),
+                '.' => {}
+                c => return Err(PatternError::InvalidCharacter(c, line_index + 1)),
+            }
+        }
+    }
+    Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Pool;
+    use crate::FixedPool;
+
+    #[test]
+    fn parse_rle_glider() {
+        let text = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+        assert_eq!(parse_rle::<Pool>(text).unwrap(), Pool::glider_south_east());
+    }
+
+    #[test]
+    fn parse_rle_rule_and_multiline() {
+        let text = "x = 7, y = 3, rule = B36/S23\nbo5b$3bo\n3b$2o2b3o!";
+        let pool = parse_rle::<Pool>(text).unwrap();
+        assert_eq!(pool.rule(), "B36/S23".parse().unwrap());
+        let mut acorn = Pool::acorn();
+        acorn.set_rule(pool.rule());
+        assert_eq!(pool, acorn);
+    }
+
+    #[test]
+    fn parse_rle_errors() {
+        assert!(matches!(
+            parse_rle::<Pool>("bo$2bo$3o!"),
+            Err(PatternError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            parse_rle::<Pool>("x = 2, y = 1\n3o!"),
+            Err(PatternError::OutOfBounds)
+        ));
+        assert!(matches!(
+            parse_rle::<Pool>("x = 2, y = 1\nbz!"),
+            Err(PatternError::InvalidCharacter('z', 2))
+        ));
+        assert!(matches!(
+            parse_rle::<Pool>("x = 2, y = 1, rule = B9\n2o!"),
+            Err(PatternError::InvalidRule(_))
+        ));
+        assert!(matches!(
+            parse_rle::<Pool>("x = 100000, y = 100000\no!"),
+            Err(PatternError::InvalidHeader(_))
+        ));
+        for overflowing in ["99999999999o!", "4294967295b2o!", "4294967295$2$o!"] {
+            assert!(matches!(
+                parse_rle::<Pool>(&format!("x = 2, y = 1\n{}", overflowing)),
+                Err(PatternError::OutOfBounds)
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_plaintext_glider() {
+        let text = "!Name: Glider\n.O\n..O\nOOO\n";
+        assert_eq!(
+            parse_plaintext::<Pool>(text).unwrap(),
+            Pool::glider_south_east()
+        );
+        assert!(matches!(
+            parse_plaintext::<Pool>(".O\nx"),
+            Err(PatternError::InvalidCharacter('x', 2))
+        ));
+        let huge = format!("{}{}", ".".repeat(100_000), "\n".repeat(100_000));
+        assert!(matches!(
+            parse_plaintext::<Pool>(&huge),
+            Err(PatternError::TooLarge(100_000, 100_000))
+        ));
+    }
+
+    #[test]
+    fn write_rle() {
+        let mut pool = Pool::new(8, 6);
+        pool += Pool::glider_south_east().with_offset(1, 2);
+        pool.set_rule("B36/S23".parse().unwrap());
+        let text = to_rle(&pool);
+        assert_eq!(text, "x = 8, y = 6, rule = B36/S23\n$3bo$4bo$2b3o!\n");
+        assert_eq!(parse_rle::<Pool>(&text).unwrap(), pool);
+
+        let mut wide = Pool::new(200, 2);
+        for column in (0..200).step_by(2) {
+            wide.set_cell(1, column, true);
+        }
+        let text = to_rle(&wide);
+        assert!(text.lines().all(|line| line.len() <= 70));
+        assert_eq!(parse_rle::<Pool>(&text).unwrap(), wide);
+    }
+
+    #[test]
+    fn write_plaintext() {
+        let text = to_plaintext(&Pool::glider_south_east());
+        assert_eq!(text, ".O.\n..O\nOOO\n");
+        assert_eq!(to_plaintext(&crate::FixedPool::glider_south_east()), text);
+        assert_eq!(
+            parse_plaintext::<Pool>(&text).unwrap(),
+            Pool::glider_south_east()
+        );
+    }
+
+    #[test]
+    fn read_fixed_pool() {
+        let text = ".O\n..O\nOOO\n";
+        let fixed: FixedPool<3, 3> = parse_plaintext(text).unwrap();
+        assert_eq!(fixed, FixedPool::glider_south_east());
+        let fixed: FixedPool<8, 4> = parse_rle("x = 3, y = 3, rule = B36/S23\nbo$2bo$3o!").unwrap();
+        assert_eq!(fixed.rule(), "B36/S23".parse().unwrap());
+        assert_eq!(fixed.population(), 5);
+        assert!(matches!(
+            parse_plaintext::<FixedPool<2, 3>>(text),
+            Err(PatternError::TooLarge(3, 3))
+        ));
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(Format::from_path("glider.cells"), Format::Plaintext);
+        assert_eq!(Format::from_path("glider.rle"), Format::Rle);
+        assert_eq!(Format::from_path("glider"), Format::Rle);
+    }
+}
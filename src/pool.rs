@@ -1,12 +1,382 @@
-use rand::Rng;
-use std::{cmp::min, fmt, ops, thread};
+#[cfg(feature = "alloc")]
+use crate::grid::{FromSize, Grid};
+#[cfg(feature = "alloc")]
+use crate::rule::Rule;
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec, vec::Vec};
+use core::{cmp::min, fmt};
+#[cfg(feature = "alloc")]
+use core::{ops, str::FromStr};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "std")]
+use std::thread;
 
-#[allow(dead_code)]
+/// How the edges of a pool behave when counting neighbors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Cells outside the pool are considered dead.
+    #[default]
+    Bounded,
+    /// Opposite edges are glued together.
+    Torus,
+}
+
+impl Topology {
+    /// Returns the 3x3 neighborhood of a cell of a pool of the given size, as read by `Rule::next_state_of`,
+    /// `is_alive(row, column)` giving the state of the cells of the pool.
+    pub(crate) fn neighborhood(
+        self,
+        height: u32,
+        width: u32,
+        row: u32,
+        column: u32,
+        is_alive: impl Fn(u32, u32) -> bool,
+    ) -> u16 {
+        let mut neighborhood = 0;
+        for i in 0..3 {
+            for j in 0..3 {
+                let cell = self.neighbor(height, width, row, column, i, j);
+                if cell.is_some_and(|(i, j)| is_alive(i, j)) {
+                    neighborhood |= 1 << (3 * i + j);
+                }
+            }
+        }
+        neighborhood
+    }
+
+    /// Returns the states of the 3x3 neighborhood of a cell in raster order, like the bits of
+    /// `neighborhood`, `state(row, column)` giving the state of the cells of the pool.
+    /// Cells beyond bounded edges are in state `0`.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn neighbor_states(
+        self,
+        height: u32,
+        width: u32,
+        row: u32,
+        column: u32,
+        state: impl Fn(u32, u32) -> u8,
+    ) -> [u8; 9] {
+        core::array::from_fn(|index| {
+            let (i, j) = (index as u32 / 3, index as u32 % 3);
+            self.neighbor(height, width, row, column, i, j)
+                .map_or(0, |(i, j)| state(i, j))
+        })
+    }
+
+    /// Returns the position of the cell at `(i, j)` in the 3x3 neighborhood of a cell,
+    /// `None` if it is beyond a bounded edge.
+    fn neighbor(
+        self,
+        height: u32,
+        width: u32,
+        row: u32,
+        column: u32,
+        i: u32,
+        j: u32,
+    ) -> Option<(u32, u32)> {
+        match self {
+            Topology::Bounded => (row + i)
+                .checked_sub(1)
+                .zip((column + j).checked_sub(1))
+                .filter(|(i, j)| *i < height && *j < width),
+            // Adding the size before subtracting one avoids underflow on the first row and column.
+            Topology::Torus => Some((
+                (row + height + i - 1) % height,
+                (column + width + j - 1) % width,
+            )),
+        }
+    }
+
+    /// Counts the alive neighbors of a cell of a pool of the given size,
+    /// `is_alive(row, column)` giving the state of the cells of the pool.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn count_alive_neighbors(
+        self,
+        height: u32,
+        width: u32,
+        row: u32,
+        column: u32,
+        is_alive: impl Fn(u32, u32) -> bool,
+    ) -> u8 {
+        (self.neighborhood(height, width, row, column, is_alive) & !Rule::CENTER).count_ones() as u8
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bounded" => Ok(Topology::Bounded),
+            "torus" => Ok(Topology::Torus),
+            _ => Err(format!(
+                "invalid topology \"{}\", expected bounded or torus",
+                s
+            )),
+        }
+    }
+}
+
+/// A rectangular area of a pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Area {
+    pub row: u32,
+    pub column: u32,
+    pub height: u32,
+    pub width: u32,
+}
+
+/// Symmetry imposed on a random fill, named after its symmetry group.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// No symmetry.
+    #[default]
+    C1,
+    /// Invariant under a rotation by 180 degrees.
+    C2,
+    /// Invariant under rotations by 90 degrees.
+    C4,
+    /// Mirrored across the horizontal axis, the bottom half reflects the top half.
+    D2Horizontal,
+    /// Mirrored across the vertical axis, the right half reflects the left half.
+    D2Vertical,
+    /// Mirrored across the main diagonal.
+    D2Diagonal,
+    /// Mirrored across both the horizontal and vertical axes.
+    D4,
+    /// Invariant under every rotation and reflection of the square.
+    D8,
+}
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::C1,
+        Symmetry::C2,
+        Symmetry::C4,
+        Symmetry::D2Horizontal,
+        Symmetry::D2Vertical,
+        Symmetry::D2Diagonal,
+        Symmetry::D4,
+        Symmetry::D8,
+    ];
+
+    /// Whether the symmetry maps rows to columns, which is only possible on a square.
+    pub fn requires_square(&self) -> bool {
+        matches!(self, Symmetry::C4 | Symmetry::D2Diagonal | Symmetry::D8)
+    }
+
+    /// Returns the images of a cell under every transformation of the symmetry group,
+    /// repeated to fill the eight entries, for an area of the given size.
+    /// The area must be square if `requires_square` is true.
+    fn orbit(&self, row: u32, column: u32, height: u32, width: u32) -> [(u32, u32); 8] {
+        let cell = (row, column);
+        let mirror_row = height - 1 - row;
+        let mirror_column = width - 1 - column;
+        let rotated_90 = (column, mirror_row);
+        let rotated_180 = (mirror_row, mirror_column);
+        let rotated_270 = (mirror_column, row);
+        let horizontal = (mirror_row, column);
+        let vertical = (row, mirror_column);
+        let diagonal = (column, row);
+        let anti_diagonal = (mirror_column, mirror_row);
+        fn repeated<const N: usize>(images: [(u32, u32); N]) -> [(u32, u32); 8] {
+            core::array::from_fn(|i| images[i % N])
+        }
+        match self {
+            Symmetry::C1 => repeated([cell]),
+            Symmetry::C2 => repeated([cell, rotated_180]),
+            Symmetry::C4 => repeated([cell, rotated_90, rotated_180, rotated_270]),
+            Symmetry::D2Horizontal => repeated([cell, horizontal]),
+            Symmetry::D2Vertical => repeated([cell, vertical]),
+            Symmetry::D2Diagonal => repeated([cell, diagonal]),
+            Symmetry::D4 => repeated([cell, horizontal, vertical, rotated_180]),
+            Symmetry::D8 => [
+                cell,
+                rotated_90,
+                rotated_180,
+                rotated_270,
+                diagonal,
+                horizontal,
+                vertical,
+                anti_diagonal,
+            ],
+        }
+    }
+}
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Symmetry::C1 => "C1",
+            Symmetry::C2 => "C2",
+            Symmetry::C4 => "C4",
+            Symmetry::D2Horizontal => "D2 horizontal",
+            Symmetry::D2Vertical => "D2 vertical",
+            Symmetry::D2Diagonal => "D2 diagonal",
+            Symmetry::D4 => "D4",
+            Symmetry::D8 => "D8",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parameters of a reproducible random fill.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RandomFill {
+    /// Seed of the random number generator, the same seed always gives the same cells.
+    pub seed: u64,
+    /// Probability for each cell to be alive, between 0 and 1.
+    pub density: f64,
+    /// Area to fill, cells outside of it are left untouched. The whole pool when `None`.
+    pub area: Option<Area>,
+    /// Symmetry of the generated cells within the area.
+    pub symmetry: Symmetry,
+}
+impl Default for RandomFill {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            density: 0.5,
+            area: None,
+            symmetry: Symmetry::default(),
+        }
+    }
+}
+impl RandomFill {
+    pub fn new(seed: u64, density: f64) -> Self {
+        Self {
+            seed,
+            density,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_area(self, area: Area) -> Self {
+        Self {
+            area: Some(area),
+            ..self
+        }
+    }
+
+    pub fn with_symmetry(self, symmetry: Symmetry) -> Self {
+        Self { symmetry, ..self }
+    }
+
+    /// Returns the cells of a pool of the given size which `apply` overwrites, the others being
+    /// left untouched : the area clipped to the pool, made square if the symmetry requires it.
+    pub(crate) fn filled_area(&self, pool_height: u32, pool_width: u32) -> Area {
+        let area = self.area.unwrap_or(Area {
+            row: 0,
+            column: 0,
+            height: pool_height,
+            width: pool_width,
+        });
+        let row = min(area.row, pool_height);
+        let column = min(area.column, pool_width);
+        let mut height = min(area.row.saturating_add(area.height), pool_height) - row;
+        let mut width = min(area.column.saturating_add(area.width), pool_width) - column;
+        if self.symmetry.requires_square() {
+            height = min(height, width);
+            width = height;
+        }
+        Area {
+            row,
+            column,
+            height,
+            width,
+        }
+    }
+
+    /// Randomizes the cells of a pool given as its rows.
+    pub(crate) fn apply<R: AsMut<[bool]>>(&self, state: &mut [R]) {
+        let pool_height = state.len() as u32;
+        let pool_width = state.first_mut().map_or(0, |row| row.as_mut().len()) as u32;
+        let Area {
+            row: row_start,
+            column: column_start,
+            height,
+            width,
+        } = self.filled_area(pool_height, pool_width);
+        let density = self.density.clamp(0.0, 1.0);
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        // Every cell takes the random state of the first cell of its orbit, which makes the orbit uniform.
+        // That cell comes first in row-major order, so it is already filled when its orbit is reached.
+        for i in 0..height {
+            for j in 0..width {
+                let random_state = rng.gen_bool(density);
+                let (orbit_row, orbit_column) = self
+                    .symmetry
+                    .orbit(i, j, height, width)
+                    .into_iter()
+                    .min()
+                    .unwrap();
+                state[(row_start + i) as usize].as_mut()[(column_start + j) as usize] =
+                    if (orbit_row, orbit_column) == (i, j) {
+                        random_state
+                    } else {
+                        state[(row_start + orbit_row) as usize].as_mut()
+                            [(column_start + orbit_column) as usize]
+                    };
+            }
+        }
+    }
+}
+
+/// Computes the next state of a grid of the given size, `next_state_rows(start, stop)` returning
+/// the next state of the rows from `start` included to `stop` excluded.
+/// The rows are split between several threads when the `std` feature is enabled.
+#[cfg(feature = "alloc")]
+pub(crate) fn compute_next_state<T: Send>(
+    height: u32,
+    width: u32,
+    next_state_rows: impl Fn(u32, u32) -> Vec<Vec<T>> + Sync,
+) -> Vec<Vec<T>> {
+    // Rows are computed whole, their width does not change how they are split.
+    let _ = width;
+    #[cfg(feature = "std")]
+    {
+        const THREAD_COUNT: u32 = 6;
+        // The line indices on which each thread will operate, thread i works from index thread_indices[i] included to thread_indices[i] excluded
+        let mut thread_indices = vec![0u32; THREAD_COUNT as usize + 1];
+        for (thread, row_index) in thread_indices.iter_mut().enumerate() {
+            *row_index = (thread as u32) * height / THREAD_COUNT;
+        }
+
+        let next_state_rows = &next_state_rows;
+        thread::scope(|s| {
+            let mut handles = Vec::with_capacity(THREAD_COUNT as usize);
+            // Spawn worker threads that handle subsets of the state, slices along rows.
+            for thread in 0..THREAD_COUNT {
+                let start = thread_indices[thread as usize];
+                let stop = thread_indices[thread as usize + 1];
+                handles.push(s.spawn(move || next_state_rows(start, stop)));
+            }
+
+            // Join worker threads, concatenate subsets to complete result.
+            let mut next_state_parts = Vec::with_capacity(THREAD_COUNT as usize);
+            for handle in handles.into_iter() {
+                next_state_parts.push(handle.join().unwrap());
+            }
+            // Return result out of scope.
+            next_state_parts.into_iter().flatten().collect()
+        })
+    }
+    #[cfg(not(feature = "std"))]
+    next_state_rows(0, height)
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Pool {
     /// Alive state of each cell, true is alive.
     state: Vec<Vec<bool>>,
+    /// Behavior of the edges.
+    topology: Topology,
+    /// Numbers of alive neighbors for which cells are born or survive.
+    rule: Rule,
 }
-#[allow(dead_code)]
+#[cfg(feature = "alloc")]
 impl fmt::Display for Pool {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for row in &self.state {
@@ -18,12 +388,12 @@ impl fmt::Display for Pool {
                 }
                 //write!(f, "{}", if *cell { "O" } else { " " }).unwrap()
             }
-            writeln!(f, "").unwrap()
+            writeln!(f).unwrap()
         }
         Ok(())
     }
 }
-#[allow(dead_code)]
+#[cfg(feature = "alloc")]
 impl ops::AddAssign<Pool> for Pool {
     fn add_assign(&mut self, other: Pool) {
         let height = min(self.height(), other.height());
@@ -37,12 +407,14 @@ impl ops::AddAssign<Pool> for Pool {
     }
 }
 
-#[allow(dead_code)]
+#[cfg(feature = "alloc")]
 impl Pool {
     pub fn new(width: u32, height: u32) -> Self {
         const DEFAULT_STATE: bool = false;
         Self {
             state: vec![vec![DEFAULT_STATE; width as usize]; height as usize],
+            topology: Topology::default(),
+            rule: Rule::default(),
         }
     }
 
@@ -50,7 +422,9 @@ impl Pool {
         data: &[[bool; HEIGHT]; WIDTH],
     ) -> Self {
         Self {
-            state: Self::convert_2d_array_to_vec(&data),
+            state: Self::convert_2d_array_to_vec(data),
+            topology: Topology::default(),
+            rule: Rule::default(),
         }
     }
 
@@ -61,7 +435,7 @@ impl Pool {
         for row in arr.iter() {
             let mut vec_row = Vec::with_capacity(HEIGHT);
             for cell in row.iter() {
-                vec_row.push(cell.to_owned());
+                vec_row.push(*cell);
             }
             ret.push(vec_row)
         }
@@ -101,13 +475,57 @@ impl Pool {
         self.state.len() as u32
     }
 
-    pub fn randomize(&mut self) {
-        let mut rng = rand::thread_rng();
-        for row in &mut self.state {
-            for cell in row {
-                *cell = rng.gen_bool(0.5);
-            }
-        }
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+    }
+
+    /// Returns the number of alive cells.
+    pub fn population(&self) -> u32 {
+        self.state.iter().flatten().filter(|cell| **cell).count() as u32
+    }
+
+    /// Returns the smallest area containing every alive cell, `None` if there is none.
+    pub fn bounding_box(&self) -> Option<Area> {
+        let rows: Vec<u32> = (0..self.height())
+            .filter(|row| self.state[*row as usize].contains(&true))
+            .collect();
+        let columns: Vec<u32> = (0..self.width())
+            .filter(|column| self.state.iter().any(|row| row[*column as usize]))
+            .collect();
+        let (first_row, last_row) = (rows.first()?, rows.last()?);
+        let (first_column, last_column) = (columns.first()?, columns.last()?);
+        Some(Area {
+            row: *first_row,
+            column: *first_column,
+            height: last_row - first_row + 1,
+            width: last_column - first_column + 1,
+        })
+    }
+
+    /// Randomizes every cell with a new random seed, which is returned so that it can be replayed.
+    #[cfg(feature = "std")]
+    pub fn randomize(&mut self) -> u64 {
+        let seed = rand::thread_rng().gen();
+        self.randomize_with(&RandomFill::new(seed, RandomFill::default().density));
+        seed
+    }
+
+    /// Randomizes the cells as described by the given fill.
+    /// Symmetries mapping rows to columns only fill the largest square at the top left of the area.
+    pub fn randomize_with(&mut self, fill: &RandomFill) {
+        fill.apply(&mut self.state);
     }
 
     pub fn clear(&mut self) {
@@ -122,86 +540,35 @@ impl Pool {
         self.state[row as usize][column as usize]
     }
 
-    fn count_alive_neighbors(&self, row: u32, column: u32) -> u8 {
-        // TODO : maybe wrap edges ?
-        let mut count = 0u8;
-        let row_start = if row > 0 { row - 1 } else { row };
-        let row_end = if row < self.height() - 1 {
-            row + 1
-        } else {
-            row
-        };
-        let column_start = if column > 0 { column - 1 } else { column };
-        let column_end = if column < self.width() - 1 {
-            column + 1
-        } else {
-            column
-        };
-        for i in row_start..(row_end + 1) {
-            for j in column_start..(column_end + 1) {
-                if self.state[i as usize][j as usize] {
-                    count += 1;
-                }
-            }
-        }
-        // Count only neighbors
-        if self.state[row as usize][column as usize] {
-            count -= 1;
-        }
-        count
+    /// Returns the 3x3 neighborhood of a cell, see `Rule::next_state_of`.
+    fn neighborhood(&self, row: u32, column: u32) -> u16 {
+        self.topology
+            .neighborhood(self.height(), self.width(), row, column, |i, j| {
+                self.state[i as usize][j as usize]
+            })
     }
 
-    /// Returns the new state of a given cell, given its current state and the number of alive neighbors
-    fn get_next_state(current_state: bool, neighbors_count: u8) -> bool {
-        if current_state {
-            neighbors_count == 2 || neighbors_count == 3
-        } else {
-            neighbors_count == 3
+    /// Computes the next state of the rows from `start` included to `stop` excluded.
+    fn next_state_rows(&self, start: u32, stop: u32) -> Vec<Vec<bool>> {
+        let width_part = self.width() as usize;
+        let height_part = (stop - start) as usize;
+        let mut next_state_part = vec![vec![false; width_part]; height_part];
+
+        for (i, row) in next_state_part.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                let complete_state_row_index = i as u32 + start;
+                *cell = self
+                    .rule
+                    .next_state_of(self.neighborhood(complete_state_row_index, j as u32));
+            }
         }
+        next_state_part
     }
 
+    /// Computes the next generation, on several threads when the `std` feature is enabled.
     pub fn step(&mut self) {
-        const THREAD_COUNT: u32 = 6;
-        // The line indices on which each thread will operate, thread i works from index thread_indices[i] included to thread_indices[i] excluded
-        let mut thread_indices = vec![0u32; THREAD_COUNT as usize + 1];
-        for (thread, row_index) in thread_indices.iter_mut().enumerate() {
-            *row_index = (thread as u32) * self.height() / THREAD_COUNT;
-        }
-
-        self.state = thread::scope(|s| {
-            let mut handles = Vec::with_capacity(THREAD_COUNT as usize);
-            // Spawn worker threads that handle subsets of the state, slices along rows.
-            for thread in 0..THREAD_COUNT {
-                let start = &thread_indices[thread as usize];
-                let stop = &thread_indices[thread as usize + 1];
-                handles.push(s.spawn(|| {
-                    let start = *start;
-                    let stop = *stop;
-                    // Create a part of the next state
-                    let width_part = self.width() as usize;
-                    let height_part = (stop - start) as usize;
-                    let mut next_state_part = vec![vec![false; width_part]; height_part];
-
-                    for (i, row) in next_state_part.iter_mut().enumerate() {
-                        for (j, cell) in row.iter_mut().enumerate() {
-                            let complete_state_row_index = i as u32 + start;
-                            *cell = Self::get_next_state(
-                                self.state[complete_state_row_index as usize][j],
-                                self.count_alive_neighbors(complete_state_row_index, j as u32),
-                            );
-                        }
-                    }
-                    next_state_part
-                }));
-            }
-
-            // Join worker threads, concatenate subsets to complete result.
-            let mut next_state_parts = Vec::with_capacity(THREAD_COUNT as usize);
-            for handle in handles.into_iter() {
-                next_state_parts.push(handle.join().unwrap());
-            }
-            // Return result out of scope.
-            next_state_parts.into_iter().flatten().collect()
+        self.state = compute_next_state(self.height(), self.width(), |start, stop| {
+            self.next_state_rows(start, stop)
         });
     }
 
@@ -219,14 +586,88 @@ impl Pool {
                     self.state[i as usize][j as usize];
             }
         }
-        return result;
+        result
     }
 
-    // TODO fn rotated(&self, angle)->Pool
-    // TODO fn mirrored(&self, horizontal:bool, vertical:bool)->Pool
+    /// Returns the pool mirrored across its horizontal axis, which flips the rows,
+    /// and/or across its vertical axis, which flips the columns.
+    pub fn mirrored(&self, horizontal: bool, vertical: bool) -> Pool {
+        let mut result = self.clone();
+        if horizontal {
+            result.state.reverse();
+        }
+        if vertical {
+            for row in &mut result.state {
+                row.reverse();
+            }
+        }
+        result
+    }
+
+    /// Returns the pool rotated by 90 degrees clockwise, its width and height being swapped.
+    pub fn rotated_clockwise(&self) -> Pool {
+        let mut result = Pool::new(self.height(), self.width());
+        result.topology = self.topology;
+        result.rule = self.rule;
+        for i in 0..result.height() {
+            for j in 0..result.width() {
+                result.state[i as usize][j as usize] =
+                    self.state[(self.height() - 1 - j) as usize][i as usize];
+            }
+        }
+        result
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Grid for Pool {
+    fn width(&self) -> u32 {
+        Pool::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        Pool::height(self)
+    }
+
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    fn get_cell(&self, row: u32, column: u32) -> bool {
+        Pool::get_cell(self, row, column)
+    }
+
+    fn set_cell(&mut self, row: u32, column: u32, state: bool) {
+        Pool::set_cell(self, row, column, state)
+    }
+
+    fn step(&mut self) {
+        Pool::step(self)
+    }
+
+    fn population(&self) -> u32 {
+        Pool::population(self)
+    }
+
+    fn bounding_box(&self) -> Option<Area> {
+        Pool::bounding_box(self)
+    }
+
+    fn clear(&mut self) {
+        Pool::clear(self)
+    }
 }
 
-#[cfg(test)]
+#[cfg(feature = "alloc")]
+impl FromSize for Pool {
+    fn from_size(width: u32, height: u32, rule: Rule) -> Option<Self> {
+        let mut pool = Pool::new(width, height);
+        pool.set_rule(rule);
+        Some(pool)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
 
@@ -247,6 +688,163 @@ mod tests {
         )
     }
 
+    #[test]
+    fn step_torus() {
+        let mut pool = Pool::new(4, 4);
+        pool.set_topology(Topology::Torus);
+        // Vertical blinker crossing the top and bottom edges.
+        pool.set_cell(3, 1, true);
+        pool.set_cell(0, 1, true);
+        pool.set_cell(1, 1, true);
+        pool.step();
+        assert_eq!(
+            pool.state,
+            vec![
+                vec![true, true, true, false],
+                vec![false, false, false, false],
+                vec![false, false, false, false],
+                vec![false, false, false, false]
+            ]
+        )
+    }
+
+    #[test]
+    fn randomize_with_seed() {
+        let mut pool = Pool::new(20, 10);
+        let mut other = Pool::new(20, 10);
+        pool.randomize_with(&RandomFill::new(12, 0.5));
+        other.randomize_with(&RandomFill::new(12, 0.5));
+        assert_eq!(pool, other);
+        other.randomize_with(&RandomFill::new(13, 0.5));
+        assert_ne!(pool, other);
+    }
+
+    #[test]
+    fn randomize_with_density() {
+        let mut pool = Pool::new(20, 10);
+        pool.randomize_with(&RandomFill::new(3, 1.0));
+        assert_eq!(pool.population(), 200);
+        pool.randomize_with(&RandomFill::new(3, 0.0));
+        assert_eq!(pool.population(), 0);
+        pool.randomize_with(&RandomFill::new(3, 0.2));
+        assert!(pool.population() > 10 && pool.population() < 80);
+    }
+
+    #[test]
+    fn randomize_with_area() {
+        let mut pool = Pool::new(10, 10);
+        let area = Area {
+            row: 2,
+            column: 3,
+            height: 4,
+            width: 20,
+        };
+        pool.randomize_with(&RandomFill::new(5, 1.0).with_area(area));
+        assert_eq!(pool.population(), 4 * 7);
+        assert!(pool.get_cell(2, 3) && pool.get_cell(5, 9));
+        assert!(!pool.get_cell(1, 3) && !pool.get_cell(6, 3) && !pool.get_cell(2, 2));
+    }
+
+    #[test]
+    fn randomize_with_symmetry() {
+        for symmetry in Symmetry::ALL {
+            for (width, height) in [(10, 10), (11, 11), (9, 6)] {
+                let mut pool = Pool::new(width, height);
+                pool.randomize_with(&RandomFill::new(8, 0.5).with_symmetry(symmetry));
+                let (height, width) = if symmetry.requires_square() {
+                    (min(height, width), min(height, width))
+                } else {
+                    (height, width)
+                };
+                for i in 0..height {
+                    for j in 0..width {
+                        for (row, column) in symmetry.orbit(i, j, height, width) {
+                            assert_eq!(
+                                pool.get_cell(i, j),
+                                pool.get_cell(row, column),
+                                "{} symmetry broken at ({}, {}) on {}x{}",
+                                symmetry,
+                                i,
+                                j,
+                                width,
+                                height
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn randomize_with_symmetry_transforms() {
+        let mut pool = Pool::new(8, 8);
+        pool.randomize_with(&RandomFill::new(21, 0.5).with_symmetry(Symmetry::D8));
+        for i in 0..8 {
+            for j in 0..8 {
+                // Transposition, horizontal and vertical mirrors, rotation by 90 degrees.
+                assert_eq!(pool.get_cell(i, j), pool.get_cell(j, i));
+                assert_eq!(pool.get_cell(i, j), pool.get_cell(7 - i, j));
+                assert_eq!(pool.get_cell(i, j), pool.get_cell(i, 7 - j));
+                assert_eq!(pool.get_cell(i, j), pool.get_cell(j, 7 - i));
+            }
+        }
+
+        pool.randomize_with(&RandomFill::new(21, 0.5).with_symmetry(Symmetry::C2));
+        let mut asymmetric = false;
+        for i in 0..8 {
+            for j in 0..8 {
+                assert_eq!(pool.get_cell(i, j), pool.get_cell(7 - i, 7 - j));
+                asymmetric |= pool.get_cell(i, j) != pool.get_cell(i, 7 - j);
+            }
+        }
+        // C2 does not imply a mirror symmetry.
+        assert!(asymmetric);
+    }
+
+    #[test]
+    fn step_with_rule() {
+        // Under B1/S, a single cell gives birth to a ring around it and dies.
+        let mut pool = Pool::new(5, 5);
+        pool.set_rule("B1/S".parse().unwrap());
+        pool.set_cell(2, 2, true);
+        pool.step();
+        assert_eq!(pool.population(), 8);
+        assert!(!pool.get_cell(2, 2));
+    }
+
+    #[test]
+    fn transforms() {
+        let glider = Pool::glider_south_east();
+        let rotated = glider.rotated_clockwise();
+        // Heading South West.
+        assert_eq!(
+            rotated,
+            Pool::from_array(&[
+                [true, false, false],
+                [true, false, true],
+                [true, true, false],
+            ])
+        );
+        // Rotating by 180 degrees then 270 degrees is a quarter turn.
+        assert_eq!(
+            rotated,
+            glider
+                .mirrored(true, true)
+                .rotated_clockwise()
+                .rotated_clockwise()
+                .rotated_clockwise()
+        );
+        assert_eq!(glider.mirrored(true, true).mirrored(true, true), glider);
+
+        let acorn = Pool::acorn().rotated_clockwise();
+        assert_eq!((acorn.width(), acorn.height()), (3, 7));
+        assert_eq!(
+            acorn.rotated_clockwise(),
+            Pool::acorn().mirrored(true, true)
+        );
+    }
+
     #[test]
     fn with_offset() {
         let mut pool = Pool::new(2, 1);
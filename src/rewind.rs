@@ -0,0 +1,169 @@
+use crate::history::Change;
+use crate::pool::Pool;
+use alloc::collections::VecDeque;
+
+/// Ring buffer of the most recent generations, stored as the cells toggled by each step,
+/// so that the pool can be stepped backward and forward through them.
+pub struct Timeline {
+    /// Change `i` turns generation `first_generation + i` into the next one.
+    changes: VecDeque<Change>,
+    first_generation: u64,
+    /// Index of the current generation, from `0` to `changes.len()` included.
+    position: usize,
+    capacity: usize,
+}
+
+impl Timeline {
+    /// Creates a timeline remembering at most `capacity` steps, starting from the given generation.
+    pub fn new(capacity: usize, generation: u64) -> Self {
+        Self {
+            changes: VecDeque::new(),
+            first_generation: generation,
+            position: 0,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Forgets every recorded generation, the pool was modified outside of a step.
+    pub fn reset(&mut self, generation: u64) {
+        self.changes.clear();
+        self.first_generation = generation;
+        self.position = 0;
+    }
+
+    /// Records the step from `previous` to `current`, which was computed at the current position.
+    /// Generations which were after the current position are forgotten.
+    pub fn record(&mut self, previous: &Pool, current: &Pool) {
+        let generation = self.current_generation();
+        self.changes.truncate(self.position);
+        if self.changes.len() == self.capacity {
+            self.changes.pop_front();
+            self.first_generation += 1;
+            self.position -= 1;
+        }
+        self.changes.push_back(Change::between(
+            previous,
+            generation,
+            current,
+            generation + 1,
+        ));
+        self.position += 1;
+    }
+
+    /// Reverts the pool to the previous recorded generation, returns false if there is none.
+    pub fn back(&mut self, pool: &mut Pool) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        self.changes[self.position].toggle(pool);
+        true
+    }
+
+    /// Moves the pool to the next recorded generation, returns false if there is none.
+    pub fn forward(&mut self, pool: &mut Pool) -> bool {
+        if self.position == self.changes.len() {
+            return false;
+        }
+        self.changes[self.position].toggle(pool);
+        self.position += 1;
+        true
+    }
+
+    /// Moves the pool to the given generation, clamped to the recorded ones.
+    pub fn seek(&mut self, pool: &mut Pool, generation: u64) {
+        let target = generation.clamp(self.first_generation(), self.last_generation());
+        while self.current_generation() > target {
+            self.back(pool);
+        }
+        while self.current_generation() < target {
+            self.forward(pool);
+        }
+    }
+
+    pub fn first_generation(&self) -> u64 {
+        self.first_generation
+    }
+
+    pub fn last_generation(&self) -> u64 {
+        self.first_generation + self.changes.len() as u64
+    }
+
+    pub fn current_generation(&self) -> u64 {
+        self.first_generation + self.position as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an acorn and its following generations.
+    fn acorn_generations(count: usize) -> Vec<Pool> {
+        let mut pool = Pool::new(30, 30);
+        pool += Pool::acorn().with_offset(12, 10);
+        let mut generations = vec![pool.clone()];
+        for _ in 0..count {
+            pool.step();
+            generations.push(pool.clone());
+        }
+        generations
+    }
+
+    fn record_all(timeline: &mut Timeline, generations: &[Pool]) {
+        for pair in generations.windows(2) {
+            timeline.record(&pair[0], &pair[1]);
+        }
+    }
+
+    #[test]
+    fn back_and_forward() {
+        let generations = acorn_generations(10);
+        let mut timeline = Timeline::new(100, 0);
+        record_all(&mut timeline, &generations);
+        let mut pool = generations[10].clone();
+
+        for generation in (0..10).rev() {
+            assert!(timeline.back(&mut pool));
+            assert_eq!(pool, generations[generation]);
+        }
+        assert!(!timeline.back(&mut pool));
+        assert!(timeline.forward(&mut pool));
+        assert_eq!(pool, generations[1]);
+
+        timeline.seek(&mut pool, 7);
+        assert_eq!(timeline.current_generation(), 7);
+        assert_eq!(pool, generations[7]);
+    }
+
+    #[test]
+    fn capacity_forgets_oldest() {
+        let generations = acorn_generations(10);
+        let mut timeline = Timeline::new(4, 0);
+        record_all(&mut timeline, &generations);
+        assert_eq!(timeline.first_generation(), 6);
+        assert_eq!(timeline.last_generation(), 10);
+
+        let mut pool = generations[10].clone();
+        timeline.seek(&mut pool, 0);
+        assert_eq!(timeline.current_generation(), 6);
+        assert_eq!(pool, generations[6]);
+    }
+
+    #[test]
+    fn record_forgets_future() {
+        let generations = acorn_generations(5);
+        let mut timeline = Timeline::new(100, 0);
+        record_all(&mut timeline, &generations);
+        let mut pool = generations[5].clone();
+        timeline.seek(&mut pool, 2);
+
+        let mut edited = pool.clone();
+        edited.set_cell(0, 0, true);
+        timeline.record(&pool, &edited);
+        assert_eq!(timeline.last_generation(), 3);
+        assert!(!timeline.forward(&mut edited));
+        assert!(timeline.back(&mut edited));
+        assert_eq!(edited, generations[2]);
+    }
+}
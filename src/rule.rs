@@ -0,0 +1,538 @@
+#[cfg(feature = "alloc")]
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::{error, str::FromStr};
+
+/// Hensel letters of the arrangements of 1 to 4 neighbors, with a neighborhood of each arrangement,
+/// see `Rule::next_state_of`. Arrangements of 5 to 7 neighbors have the letter of their complement.
+const LETTERS: [&[(char, u16)]; 5] = [
+    &[],
+    &[('c', 1), ('e', 2)],
+    &[
+        ('c', 5),
+        ('e', 10),
+        ('k', 33),
+        ('a', 3),
+        ('i', 40),
+        ('n', 68),
+    ],
+    &[
+        ('c', 69),
+        ('e', 42),
+        ('k', 98),
+        ('a', 11),
+        ('i', 7),
+        ('n', 13),
+        ('y', 97),
+        ('q', 70),
+        ('j', 14),
+        ('r', 41),
+    ],
+    &[
+        ('c', 325),
+        ('e', 170),
+        ('k', 99),
+        ('a', 15),
+        ('i', 45),
+        ('n', 71),
+        ('y', 78),
+        ('q', 102),
+        ('j', 106),
+        ('r', 43),
+        ('t', 101),
+        ('w', 105),
+        ('z', 108),
+    ],
+];
+
+/// Bits of the eight neighbors in a neighborhood.
+const NEIGHBORS: u16 = 0b1_1110_1111;
+
+/// Returns the Hensel letters of the arrangements of a number of neighbors.
+fn letters(count: usize) -> &'static [(char, u16)] {
+    LETTERS[count.min(8 - count)]
+}
+
+/// Returns a neighborhood of each arrangement of a number of neighbors, in the order of their letters.
+fn arrangement_neighborhood(count: usize, letter_index: usize) -> u16 {
+    match (count, letters(count).get(letter_index)) {
+        (0..=4, Some((_, neighborhood))) => *neighborhood,
+        (_, Some((_, neighborhood))) => !neighborhood & NEIGHBORS,
+        (0, None) => 0,
+        (_, None) => NEIGHBORS,
+    }
+}
+
+/// Returns the rotations and reflections of a neighborhood.
+fn symmetric_images(neighborhood: u16) -> [u16; 8] {
+    core::array::from_fn(|symmetry| {
+        let mut image = 0;
+        for bit in (0..9).filter(|bit| neighborhood & (1 << bit) != 0) {
+            let (mut row, mut column) = (bit / 3 - 1, bit % 3 - 1);
+            if symmetry & 4 != 0 {
+                column = -column;
+            }
+            for _ in 0..symmetry & 3 {
+                (row, column) = (column, -row);
+            }
+            image |= 1 << ((row + 1) * 3 + column + 1);
+        }
+        image
+    })
+}
+
+/// Returns the number of alive neighbors in a neighborhood and the index of the letter of their arrangement,
+/// `0` if there is a single arrangement.
+fn arrangement(neighborhood: u16) -> (usize, usize) {
+    let neighbors = neighborhood & NEIGHBORS;
+    let count = neighbors.count_ones() as usize;
+    let images = symmetric_images(neighbors);
+    let letter_index = (0..letters(count).len())
+        .position(|index| images.contains(&arrangement_neighborhood(count, index)))
+        .unwrap_or(0);
+    (count, letter_index)
+}
+
+/// Neighbors of a cell counted by a rule.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    /// The 8 surrounding cells.
+    #[default]
+    Moore,
+    /// The 4 orthogonally adjacent cells, rules suffixed with `V`.
+    VonNeumann,
+    /// 6 cells emulating a hexagonal grid on the square one, all surrounding cells but the top right
+    /// and bottom left ones, rules suffixed with `H`.
+    Hexagonal,
+}
+
+impl Neighborhood {
+    /// Returns the bits of the neighbors in a neighborhood, see `Rule::next_state_of`.
+    pub fn mask(self) -> u16 {
+        match self {
+            Neighborhood::Moore => NEIGHBORS,
+            Neighborhood::VonNeumann => 0b0_1010_1010,
+            Neighborhood::Hexagonal => 0b1_1010_1011,
+        }
+    }
+
+    /// Returns the number of neighbors.
+    pub fn size(self) -> u8 {
+        self.mask().count_ones() as u8
+    }
+
+    /// Returns the suffix of the rules on this neighborhood.
+    fn suffix(self) -> &'static str {
+        match self {
+            Neighborhood::Moore => "",
+            Neighborhood::VonNeumann => "V",
+            Neighborhood::Hexagonal => "H",
+        }
+    }
+}
+
+/// A life-like rule, giving the arrangements of alive neighbors for which a cell is born or survives.
+///
+/// Totalistic rules only depend on the number of alive neighbors. Isotropic non-totalistic rules,
+/// written in the Hensel notation such as `B3/S2-i34q`, depend on their arrangement up to rotations
+/// and reflections. Totalistic rules may also count fewer neighbors, see `Neighborhood`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rule {
+    /// Bit `n % 64` of `table[n / 64]` is the next state of a cell whose neighborhood is `n`.
+    table: [u64; 8],
+    /// Cells counted as neighbors, cells outside of it being ignored by `table`.
+    neighborhood: Neighborhood,
+}
+impl Default for Rule {
+    /// Conway's game of life, B3/S23.
+    fn default() -> Self {
+        Self::new(&[3], &[2, 3])
+    }
+}
+
+impl Rule {
+    /// Bit of the cell itself in a neighborhood.
+    pub const CENTER: u16 = 1 << 4;
+
+    /// Returns the totalistic rule in which cells are born or survive with the given numbers of alive neighbors.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        Self::totalistic(Neighborhood::Moore, birth, survival)
+    }
+
+    /// Returns the totalistic rule counting the alive cells of the given neighborhood.
+    pub fn totalistic(neighborhood: Neighborhood, birth: &[u8], survival: &[u8]) -> Self {
+        Self::from_fn(neighborhood, |cells| {
+            let count = (cells & neighborhood.mask()).count_ones() as u8;
+            if cells & Self::CENTER != 0 {
+                survival.contains(&count)
+            } else {
+                birth.contains(&count)
+            }
+        })
+    }
+
+    fn from_fn(neighborhood: Neighborhood, next_state: impl Fn(u16) -> bool) -> Self {
+        let mut table = [0; 8];
+        for cells in 0..512 {
+            if next_state(cells) {
+                table[cells as usize / 64] |= 1 << (cells % 64);
+            }
+        }
+        Self {
+            table,
+            neighborhood,
+        }
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// Returns the new state of a cell given its 3x3 neighborhood, in which bit `3 * row + column`
+    /// is set if the cell at `(row, column)` is alive, the cell itself being at `(1, 1)`.
+    pub fn next_state_of(&self, neighborhood: u16) -> bool {
+        self.table[neighborhood as usize / 64] & (1 << (neighborhood % 64)) != 0
+    }
+
+    /// Returns the new state of a cell, given its current state and the number of alive neighbors,
+    /// `None` if it also depends on their arrangement or if there cannot be that many neighbors.
+    /// `next_state_of` handles non-totalistic rules.
+    pub fn next_state(&self, current_state: bool, neighbors_count: u8) -> Option<bool> {
+        let mask = self.neighborhood.mask();
+        let center = if current_state { Self::CENTER } else { 0 };
+        let mut states = (0..512u16)
+            .filter(|neighbors| neighbors & !mask == 0)
+            .filter(|neighbors| neighbors.count_ones() == neighbors_count as u32)
+            .map(|neighbors| self.next_state_of(neighbors | center));
+        let state = states.next()?;
+        states.all(|other| other == state).then_some(state)
+    }
+
+    /// Whether the rule only depends on the number of alive neighbors.
+    pub fn is_totalistic(&self) -> bool {
+        if self.neighborhood != Neighborhood::Moore {
+            return true;
+        }
+        (0..512).all(|neighborhood| {
+            let (count, _) = arrangement(neighborhood);
+            let center = neighborhood & Self::CENTER;
+            self.next_state_of(neighborhood)
+                == self.next_state_of(arrangement_neighborhood(count, 0) | center)
+        })
+    }
+
+    /// Returns the letters of the arrangements of a number of neighbors for which a cell becomes
+    /// or stays alive, as bits in the order of `LETTERS`.
+    fn arrangements(&self, count: usize, survival: bool) -> u16 {
+        let center = if survival { Self::CENTER } else { 0 };
+        (0..letters(count).len().max(1))
+            .filter(|index| self.next_state_of(arrangement_neighborhood(count, *index) | center))
+            .fold(0, |arrangements, index| arrangements | 1 << index)
+    }
+}
+
+/// Error returned when a rulestring cannot be parsed.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseRuleError {
+    rule: String,
+    /// Examples of valid rules of the expected family.
+    expected: &'static str,
+}
+#[cfg(feature = "alloc")]
+impl ParseRuleError {
+    pub(crate) fn new(rule: &str, expected: &'static str) -> Self {
+        Self {
+            rule: rule.to_owned(),
+            expected,
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid rule \"{}\", expected e.g. {}",
+            self.rule, self.expected
+        )
+    }
+}
+#[cfg(feature = "alloc")]
+impl error::Error for ParseRuleError {}
+
+#[cfg(feature = "alloc")]
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parses a rule in the `B3/S23` notation, or in the older survival first `23/3` notation.
+    /// Counts may be followed by Hensel letters, optionally negated by `-`, as in `B3/S2-i34q`.
+    /// Totalistic rules suffixed with `V` or `H` count the von Neumann or hexagonal neighborhood.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseRuleError::new(s, "B3/S23, 23/3, B3/S2-i34q or B2/S34H");
+        // Letters of the selected arrangements of each number of neighbors.
+        let parse_counts = |part: &str| -> Result<[u16; 9], ParseRuleError> {
+            let mut selected = [0u16; 9];
+            let mut characters = part.chars().peekable();
+            while let Some(character) = characters.next() {
+                let count = match character.to_digit(10) {
+                    Some(count) if count <= 8 => count as usize,
+                    _ => return Err(error()),
+                };
+                let all_letters = (1 << letters(count).len().max(1)) - 1;
+                let negated = characters.next_if_eq(&'-').is_some();
+                let mut listed = 0u16;
+                while let Some(letter) = characters.next_if(char::is_ascii_alphabetic) {
+                    let index = letters(count)
+                        .iter()
+                        .position(|(l, _)| *l == letter.to_ascii_lowercase())
+                        .ok_or_else(error)?;
+                    listed |= 1 << index;
+                }
+                selected[count] |= match (negated, listed) {
+                    (false, 0) => all_letters,
+                    (false, listed) => listed,
+                    (true, 0) => return Err(error()),
+                    (true, listed) => all_letters & !listed,
+                };
+            }
+            Ok(selected)
+        };
+
+        let rule = s.trim();
+        let (rule, neighborhood) = match rule.strip_suffix(['V', 'v']) {
+            Some(rule) => (rule, Neighborhood::VonNeumann),
+            None => match rule.strip_suffix(['H', 'h']) {
+                Some(rule) => (rule, Neighborhood::Hexagonal),
+                None => (rule, Neighborhood::Moore),
+            },
+        };
+        let (first, second) = rule.split_once('/').ok_or_else(error)?;
+        let upper = (first.to_ascii_uppercase(), second.to_ascii_uppercase());
+        let (birth, survival) = match (upper.0.strip_prefix('B'), upper.1.strip_prefix('S')) {
+            (Some(birth), Some(survival)) => (birth.to_owned(), survival.to_owned()),
+            (None, None) => match (upper.0.strip_prefix('S'), upper.1.strip_prefix('B')) {
+                (Some(survival), Some(birth)) => (birth.to_owned(), survival.to_owned()),
+                (None, None) => (upper.1.clone(), upper.0.clone()),
+                _ => return Err(error()),
+            },
+            _ => return Err(error()),
+        };
+        let (birth, survival) = (parse_counts(&birth)?, parse_counts(&survival)?);
+        if neighborhood != Neighborhood::Moore {
+            // Without letters, every arrangement of the selected counts is selected.
+            let counts = |selected: [u16; 9]| -> Result<Vec<u8>, ParseRuleError> {
+                (0..9)
+                    .filter(|count| selected[*count] != 0)
+                    .map(|count| {
+                        let all_letters = (1 << letters(count).len().max(1)) - 1;
+                        if count as u8 > neighborhood.size() || selected[count] != all_letters {
+                            Err(error())
+                        } else {
+                            Ok(count as u8)
+                        }
+                    })
+                    .collect()
+            };
+            return Ok(Self::totalistic(
+                neighborhood,
+                &counts(birth)?,
+                &counts(survival)?,
+            ));
+        }
+        Ok(Self::from_fn(Neighborhood::Moore, |neighborhood| {
+            let (count, letter_index) = arrangement(neighborhood);
+            let selected = if neighborhood & Self::CENTER != 0 {
+                survival
+            } else {
+                birth
+            };
+            selected[count] & (1 << letter_index) != 0
+        }))
+    }
+}
+
+impl fmt::Display for Rule {
+    /// Writes the rule in the `B3/S23` notation, with Hensel letters for non-totalistic rules.
+    /// Letters are negated when it makes them shorter, as in `B3/S2-i34q`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write_counts = |f: &mut fmt::Formatter<'_>, survival: bool| -> fmt::Result {
+            if self.neighborhood != Neighborhood::Moore {
+                return (0..=self.neighborhood.size())
+                    .filter(|count| self.next_state(survival, *count) == Some(true))
+                    .try_for_each(|count| write!(f, "{}", count));
+            }
+            for count in 0..9 {
+                let arrangements = self.arrangements(count, survival);
+                if arrangements == 0 {
+                    continue;
+                }
+                write!(f, "{}", count)?;
+                let all_letters = (1 << letters(count).len().max(1)) - 1;
+                let missing = all_letters & !arrangements;
+                let written = if missing == 0 {
+                    0
+                } else if missing.count_ones() < arrangements.count_ones() {
+                    write!(f, "-")?;
+                    missing
+                } else {
+                    arrangements
+                };
+                for (index, (letter, _)) in letters(count).iter().enumerate() {
+                    if written & (1 << index) != 0 {
+                        write!(f, "{}", letter)?;
+                    }
+                }
+            }
+            Ok(())
+        };
+        write!(f, "B")?;
+        write_counts(f, false)?;
+        write!(f, "/S")?;
+        write_counts(f, true)?;
+        write!(f, "{}", self.neighborhood.suffix())
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::pool::Pool;
+    use alloc::collections::BTreeSet;
+
+    #[test]
+    fn parse() {
+        assert_eq!("B3/S23".parse(), Ok(Rule::default()));
+        assert_eq!("b3/s23".parse(), Ok(Rule::default()));
+        assert_eq!("S23/B3".parse(), Ok(Rule::default()));
+        assert_eq!("23/3".parse(), Ok(Rule::default()));
+        assert_eq!("B36/S23".parse(), Ok(Rule::new(&[3, 6], &[2, 3])));
+        assert_eq!("B2/S".parse(), Ok(Rule::new(&[2], &[])));
+        assert!("B9/S23".parse::<Rule>().is_err());
+        assert!("B3S23".parse::<Rule>().is_err());
+        assert!("B3/B23".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Rule::default().to_string(), "B3/S23");
+        assert_eq!("34/34".parse::<Rule>().unwrap().to_string(), "B34/S34");
+    }
+
+    #[test]
+    fn arrangements() {
+        // Each arrangement is kept by rotations and reflections, and there are 51 of them.
+        let mut distinct = BTreeSet::new();
+        for neighborhood in 0..512u16 {
+            let (count, letter_index) = arrangement(neighborhood);
+            assert!(letter_index < letters(count).len().max(1));
+            for image in symmetric_images(neighborhood) {
+                assert_eq!(arrangement(image), (count, letter_index));
+            }
+            distinct.insert((count, letter_index));
+        }
+        assert_eq!(distinct.len(), 51);
+    }
+
+    #[test]
+    fn parse_hensel() {
+        let tlife: Rule = "B3/S2-i34q".parse().unwrap();
+        assert_eq!(tlife.to_string(), "B3/S2-i34q");
+        assert!(!tlife.is_totalistic());
+        // 26 neighborhoods with 2 neighbors, all 56 with 3 and the 4 with a 4q arrangement.
+        let survivals = (0..512u16)
+            .filter(|neighborhood| neighborhood & Rule::CENTER != 0)
+            .filter(|neighborhood| tlife.next_state_of(*neighborhood))
+            .count();
+        assert_eq!(survivals, 26 + 56 + 4);
+        assert_eq!("b3/s2ceKAIN3".parse(), Ok(Rule::default()));
+        assert!(Rule::default().is_totalistic());
+        assert_eq!("B2-a/S12".parse::<Rule>().unwrap().to_string(), "B2-a/S12");
+        assert_eq!("B5c6-k/S".parse::<Rule>().unwrap().to_string(), "B5c6-k/S");
+        assert_eq!("B2ce/S".parse::<Rule>().unwrap().to_string(), "B2ce/S");
+        for invalid in ["B2x/S", "B1k/S", "B3/S2-", "B3-/S23"] {
+            assert!(invalid.parse::<Rule>().is_err(), "{} accepted", invalid);
+        }
+    }
+
+    #[test]
+    fn next_state_by_count() {
+        assert_eq!(Rule::default().next_state(true, 2), Some(true));
+        assert_eq!(Rule::default().next_state(false, 2), Some(false));
+        assert_eq!(Rule::default().next_state(false, 9), None);
+        let hexagonal: Rule = "B2/S34H".parse().unwrap();
+        assert_eq!(hexagonal.next_state(false, 2), Some(true));
+        assert_eq!(hexagonal.next_state(false, 7), None);
+
+        // With 2 neighbors, a cell survives in the 2c arrangement but not in the 2i one.
+        let tlife: Rule = "B3/S2-i34q".parse().unwrap();
+        let corners = Rule::CENTER | 0b0_0000_0101;
+        let opposite = Rule::CENTER | 0b0_1000_0010;
+        assert!(tlife.next_state_of(corners));
+        assert!(!tlife.next_state_of(opposite));
+        assert_eq!(tlife.next_state(true, 2), None);
+        assert_eq!(tlife.next_state(false, 3), Some(true));
+    }
+
+    #[test]
+    fn parse_neighborhoods() {
+        let hexagonal: Rule = "B2/S34H".parse().unwrap();
+        assert_eq!(
+            hexagonal,
+            Rule::totalistic(Neighborhood::Hexagonal, &[2], &[3, 4])
+        );
+        assert_eq!(hexagonal.to_string(), "B2/S34H");
+        assert_eq!("s013/b2v".parse::<Rule>().unwrap().to_string(), "B2/S013V");
+        for invalid in ["B5/S23V", "B7/S23H", "B2a/S23V", "B3/S23X"] {
+            assert!(invalid.parse::<Rule>().is_err(), "{} accepted", invalid);
+        }
+    }
+
+    #[test]
+    fn ignored_neighbors() {
+        // Only the 4 orthogonal neighbors count, the corners are ignored.
+        let von_neumann: Rule = "B1/SV".parse().unwrap();
+        assert!(von_neumann.next_state_of(0b0_0000_0010));
+        assert!(!von_neumann.next_state_of(0b0_0000_0001));
+        assert!(von_neumann.next_state_of(0b1_0000_0101 | 0b0_1000_0000));
+        // The top right and bottom left neighbors are not part of the hexagonal neighborhood.
+        let hexagonal: Rule = "B1/SH".parse().unwrap();
+        assert!(hexagonal.next_state_of(0b1_0000_0000));
+        assert!(!hexagonal.next_state_of(0b0_0000_0100));
+        assert!(!hexagonal.next_state_of(0b0_0100_0000));
+    }
+
+    #[test]
+    fn tlife() {
+        let tlife: Rule = "B3/S2-i34q".parse().unwrap();
+        let pool_with = |pattern: Pool| {
+            let mut pool = Pool::new(8, 8);
+            pool += pattern.with_offset(2, 2);
+            pool.set_rule(tlife);
+            pool
+        };
+
+        // Block and beehive are still lifes, their cells having 3, 2c or 2k neighbors.
+        for still_life in [
+            Pool::from_array(&[[true, true], [true, true]]),
+            Pool::from_array(&[
+                [false, true, true, false],
+                [true, false, false, true],
+                [false, true, true, false],
+            ]),
+        ] {
+            let mut pool = pool_with(still_life);
+            let initial = pool.clone();
+            pool.step();
+            assert_eq!(pool, initial);
+        }
+
+        // Without 2i survival, the center of a blinker dies and it vanishes.
+        let mut blinker = pool_with(Pool::from_array(&[[true, true, true]]));
+        blinker.step();
+        assert_eq!(blinker.population(), 2);
+        blinker.step();
+        assert_eq!(blinker.population(), 0);
+    }
+}
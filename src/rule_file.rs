@@ -0,0 +1,883 @@
+//! Golly `.rule` files, which describe multistate rules with a `@TABLE` of transitions or a
+//! `@TREE` of the states of the neighbors, and their colors in `@COLORS`.
+
+use crate::grid::StateGrid;
+use crate::pool::{compute_next_state, Pool, Topology};
+use crate::rule::Neighborhood;
+use alloc::{borrow::ToOwned, collections::BTreeSet, format, string::String, vec, vec::Vec};
+use core::{error, fmt, str::FromStr};
+#[cfg(feature = "std")]
+use std::{fs, io, path::Path};
+
+/// Error returned when a rule file cannot be read.
+#[derive(Debug)]
+pub enum RuleFileError {
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The file has neither a `@TABLE` nor a `@TREE` section.
+    MissingTransitions,
+    /// A malformed line, with its line number starting at 1 and what is wrong with it.
+    InvalidLine(usize, String),
+}
+impl fmt::Display for RuleFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            RuleFileError::Io(error) => write!(f, "{}", error),
+            RuleFileError::MissingTransitions => write!(f, "no @TABLE or @TREE section"),
+            RuleFileError::InvalidLine(line, reason) => write!(f, "{} on line {}", reason, line),
+        }
+    }
+}
+impl error::Error for RuleFileError {}
+#[cfg(feature = "std")]
+impl From<io::Error> for RuleFileError {
+    fn from(error: io::Error) -> Self {
+        RuleFileError::Io(error)
+    }
+}
+
+/// Reads a `.rule` file.
+#[cfg(feature = "std")]
+pub fn load<P: AsRef<Path>>(path: P) -> Result<RuleFile, RuleFileError> {
+    fs::read_to_string(path)?.parse()
+}
+
+/// Set of states, bit `state` being set for the states it contains.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct StateSet([u64; 4]);
+
+impl StateSet {
+    fn single(state: u8) -> Self {
+        let mut set = Self::default();
+        set.insert(state);
+        set
+    }
+
+    fn insert(&mut self, state: u8) {
+        self.0[state as usize / 64] |= 1 << (state % 64);
+    }
+
+    fn iter(self) -> impl Iterator<Item = u8> {
+        (0..=u8::MAX).filter(move |state| self.0[*state as usize / 64] & 1 << (state % 64) != 0)
+    }
+}
+
+/// Transitions of a `@TABLE` section, the first one matching a neighborhood giving the new state.
+#[derive(Clone, Debug)]
+struct Table {
+    /// Cells of the 3x3 neighborhood in the order of the table, as indices in raster order.
+    cells: &'static [usize],
+    /// Number of `u64` words needed for one bit per transition.
+    words: usize,
+    /// Word `w` of the transitions allowing state `s` at position `p` of the table,
+    /// at index `(p * states + s) * words + w`.
+    matches: Vec<u64>,
+    /// New state given by each transition.
+    outputs: Vec<u8>,
+}
+
+impl Table {
+    fn next_state(&self, states: u8, cells: [u8; 9]) -> u8 {
+        for word in 0..self.words {
+            let mut transitions = !0;
+            for (position, cell) in self.cells.iter().enumerate() {
+                let index = (position * states as usize + cells[*cell] as usize) * self.words;
+                transitions &= self.matches[index + word];
+                if transitions == 0 {
+                    break;
+                }
+            }
+            if transitions != 0 {
+                return self.outputs[word * 64 + transitions.trailing_zeros() as usize];
+            }
+        }
+        cells[4]
+    }
+}
+
+/// Decision tree of a `@TREE` section, walked down with the state of each cell.
+#[derive(Clone, Debug)]
+struct Tree {
+    /// Cells of the 3x3 neighborhood in the order of the tree levels, as indices in raster order.
+    cells: &'static [usize],
+    /// Child `s` of node `n`, at index `n * states + s`. Children of the last level are new states.
+    children: Vec<u32>,
+    root: u32,
+}
+
+impl Tree {
+    fn next_state(&self, states: u8, cells: [u8; 9]) -> u8 {
+        self.cells.iter().fold(self.root, |node, cell| {
+            self.children[node as usize * states as usize + cells[*cell] as usize]
+        }) as u8
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Transitions {
+    Table(Table),
+    Tree(Tree),
+}
+
+/// A rule read from a Golly `.rule` file.
+#[derive(Clone, Debug)]
+pub struct RuleFile {
+    name: String,
+    /// Number of states, `0` being the empty state.
+    states: u8,
+    transitions: Transitions,
+    /// Color of each state given by `@COLORS`, if any.
+    colors: Vec<Option<[u8; 3]>>,
+}
+
+impl RuleFile {
+    /// Name given after `@RULE`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// Returns the `[red, green, blue]` color of a state given by `@COLORS`, if any.
+    pub fn color(&self, state: u8) -> Option<[u8; 3]> {
+        self.colors.get(state as usize).copied().flatten()
+    }
+
+    /// Returns the new state of a cell given the states of its 3x3 neighborhood in raster order,
+    /// the cell itself being `cells[4]`. Cells which match no transition of a table keep their state.
+    pub fn next_state(&self, cells: [u8; 9]) -> u8 {
+        match &self.transitions {
+            Transitions::Table(table) => table.next_state(self.states, cells),
+            Transitions::Tree(tree) => tree.next_state(self.states, cells),
+        }
+    }
+}
+
+/// Lines of a section with their line numbers, without comments and blank lines.
+type Lines<'a> = Vec<(usize, &'a str)>;
+
+fn invalid(line: usize, reason: impl Into<String>) -> RuleFileError {
+    RuleFileError::InvalidLine(line, reason.into())
+}
+
+/// Returns the value of a `key:value` or `key=value` line, `None` for another key.
+fn setting<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let (name, value) = line.split_once([':', '='])?;
+    (name.trim() == key).then(|| value.trim())
+}
+
+fn parse_number<T: FromStr>(line: usize, text: &str) -> Result<T, RuleFileError> {
+    text.trim()
+        .parse()
+        .map_err(|_| invalid(line, format!("invalid number \"{}\"", text.trim())))
+}
+
+/// Returns, for each symmetric image of a transition, the neighbor each neighbor is taken from.
+/// Neighbors are numbered clockwise, as in the transitions.
+fn symmetries(line: usize, name: &str, size: usize) -> Result<Vec<Vec<usize>>, RuleFileError> {
+    let rotation = |steps: usize| -> Vec<usize> { (0..size).map(|k| (k + steps) % size).collect() };
+    let reflection = |permutation: Vec<usize>| -> Vec<usize> {
+        permutation.iter().map(|k| (size - k) % size).collect()
+    };
+    let unsupported = || invalid(line, format!("unsupported symmetries \"{}\"", name));
+    match name {
+        "none" => Ok(vec![rotation(0)]),
+        "reflect_horizontal" => Ok(vec![rotation(0), reflection(rotation(0))]),
+        "permute" => {
+            // Heap's algorithm.
+            let mut permutation = rotation(0);
+            let mut counters = vec![0; size];
+            let mut permutations = vec![permutation.clone()];
+            let mut index = 1;
+            while index < size {
+                if counters[index] < index {
+                    let other = if index % 2 == 0 { 0 } else { counters[index] };
+                    permutation.swap(other, index);
+                    permutations.push(permutation.clone());
+                    counters[index] += 1;
+                    index = 1;
+                } else {
+                    counters[index] = 0;
+                    index += 1;
+                }
+            }
+            Ok(permutations)
+        }
+        _ => {
+            let (rotations, reflect) = match name.strip_suffix("reflect") {
+                Some(rotations) => (rotations, true),
+                None => (name, false),
+            };
+            let rotations: usize = rotations
+                .strip_prefix("rotate")
+                .and_then(|count| count.parse().ok())
+                .filter(|count| *count > 0 && size.is_multiple_of(*count))
+                .ok_or_else(unsupported)?;
+            let rotated = (0..rotations).map(|index| rotation(index * size / rotations));
+            Ok(if reflect {
+                rotated
+                    .flat_map(|rotated| [rotated.clone(), reflection(rotated)])
+                    .collect()
+            } else {
+                rotated.collect()
+            })
+        }
+    }
+}
+
+/// Parses a `@TABLE` section, returning the number of states and the transitions.
+fn parse_table(lines: &Lines) -> Result<(u8, Transitions), RuleFileError> {
+    let mut states: Option<u8> = None;
+    let mut neighborhood: Option<Neighborhood> = None;
+    let mut symmetry_name: Option<&str> = None;
+    let mut variables: Vec<(&str, StateSet)> = Vec::new();
+    let mut transitions: Vec<(Vec<StateSet>, u8)> = Vec::new();
+
+    for &(line, text) in lines {
+        if let Some(value) = setting(text, "n_states") {
+            states = Some(
+                parse_number::<u8>(line, value)
+                    .ok()
+                    .filter(|states| *states >= 2)
+                    .ok_or_else(|| invalid(line, "n_states must be between 2 and 255"))?,
+            );
+            continue;
+        }
+        if let Some(value) = setting(text, "neighborhood") {
+            neighborhood = Some(match value.to_ascii_lowercase().as_str() {
+                "moore" => Neighborhood::Moore,
+                "vonneumann" => Neighborhood::VonNeumann,
+                "hexagonal" => Neighborhood::Hexagonal,
+                _ => {
+                    return Err(invalid(
+                        line,
+                        format!("unsupported neighborhood \"{}\"", value),
+                    ))
+                }
+            });
+            continue;
+        }
+        if let Some(value) = setting(text, "symmetries") {
+            symmetry_name = Some(value);
+            continue;
+        }
+        let (Some(states), Some(neighborhood), Some(symmetry_name)) =
+            (states, neighborhood, symmetry_name)
+        else {
+            return Err(invalid(
+                line,
+                "n_states, neighborhood and symmetries must come first",
+            ));
+        };
+        let state = |token: &str| -> Result<u8, RuleFileError> {
+            parse_number::<u8>(line, token)
+                .ok()
+                .filter(|state| *state < states)
+                .ok_or_else(|| invalid(line, format!("invalid state \"{}\"", token)))
+        };
+        // Index of the last definition of a variable, later definitions replacing earlier ones.
+        let variable = |variables: &[(&str, StateSet)], token: &str| {
+            variables.iter().rposition(|(name, _)| *name == token)
+        };
+
+        if let Some(definition) = text.strip_prefix("var ") {
+            let (name, values) = definition
+                .split_once('=')
+                .ok_or_else(|| invalid(line, "expected var name={...}"))?;
+            let values = values
+                .trim()
+                .strip_prefix('{')
+                .and_then(|values| values.strip_suffix('}'))
+                .ok_or_else(|| invalid(line, "expected var name={...}"))?;
+            let mut set = StateSet::default();
+            for value in values.split(',').map(str::trim) {
+                match variable(&variables, value) {
+                    Some(index) => {
+                        for state in variables[index].1.iter() {
+                            set.insert(state);
+                        }
+                    }
+                    None => set.insert(state(value)?),
+                }
+            }
+            variables.push((name.trim(), set));
+            continue;
+        }
+
+        // A transition, with or without commas between single digit states.
+        let tokens: Vec<&str> = if text.contains(',') {
+            text.split(',').map(str::trim).collect()
+        } else if text.is_ascii() {
+            (0..text.len())
+                .map(|index| &text[index..index + 1])
+                .collect()
+        } else {
+            return Err(invalid(line, "invalid transition"));
+        };
+        let size = neighborhood.size() as usize;
+        if tokens.len() != size + 2 {
+            return Err(invalid(
+                line,
+                format!("expected {} states per transition", size + 2),
+            ));
+        }
+        let entries = tokens
+            .iter()
+            .map(|token| match variable(&variables, token) {
+                Some(index) => Ok(Err(index)),
+                None => state(token).map(Ok),
+            })
+            .collect::<Result<Vec<Result<u8, usize>>, _>>()?;
+        // Variables appearing more than once are bound : they take the same state everywhere.
+        let bound: Vec<usize> = entries
+            .iter()
+            .filter_map(|entry| entry.err())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|index| {
+                entries
+                    .iter()
+                    .filter(|entry| **entry == Err(*index))
+                    .count()
+                    > 1
+            })
+            .collect();
+        if let Some(Err(index)) = entries.last() {
+            if !bound.contains(index) {
+                return Err(invalid(line, "the new state is a variable not used before"));
+            }
+        }
+        let choices: Vec<Vec<u8>> = bound
+            .iter()
+            .map(|index| variables[*index].1.iter().collect())
+            .collect();
+        let permutations = symmetries(line, symmetry_name, size)?;
+
+        let mut images = BTreeSet::new();
+        let mut choice = vec![0; bound.len()];
+        loop {
+            let resolve = |entry: &Result<u8, usize>| match entry {
+                Ok(state) => StateSet::single(*state),
+                Err(index) => match bound.iter().position(|bound| bound == index) {
+                    Some(position) => StateSet::single(choices[position][choice[position]]),
+                    None => variables[*index].1,
+                },
+            };
+            let inputs: Vec<StateSet> = entries[..size + 1].iter().map(resolve).collect();
+            let output = resolve(&entries[size + 1]).iter().next().unwrap_or(0);
+            for permutation in &permutations {
+                let image: Vec<StateSet> = core::iter::once(inputs[0])
+                    .chain(permutation.iter().map(|k| inputs[1 + k]))
+                    .collect();
+                if images.insert(image.clone()) {
+                    transitions.push((image, output));
+                }
+            }
+            // Next combination of the bound variables, like an odometer.
+            let Some(position) =
+                (0..bound.len()).find(|position| choice[*position] + 1 < choices[*position].len())
+            else {
+                break;
+            };
+            choice[position] += 1;
+            choice[..position].fill(0);
+        }
+    }
+
+    let (Some(states), Some(neighborhood)) = (states, neighborhood) else {
+        let line = lines.last().map_or(0, |(line, _)| *line);
+        return Err(invalid(line, "missing n_states or neighborhood"));
+    };
+    let cells: &'static [usize] = match neighborhood {
+        Neighborhood::Moore => &[4, 1, 2, 5, 8, 7, 6, 3, 0],
+        Neighborhood::VonNeumann => &[4, 1, 5, 7, 3],
+        Neighborhood::Hexagonal => &[4, 1, 5, 8, 7, 3, 0],
+    };
+    let words = transitions.len().div_ceil(64);
+    let mut matches = vec![0; cells.len() * states as usize * words];
+    for (index, (inputs, _)) in transitions.iter().enumerate() {
+        for (position, set) in inputs.iter().enumerate() {
+            for state in set.iter() {
+                matches[(position * states as usize + state as usize) * words + index / 64] |=
+                    1 << (index % 64);
+            }
+        }
+    }
+    let table = Table {
+        cells,
+        words,
+        matches,
+        outputs: transitions.into_iter().map(|(_, output)| output).collect(),
+    };
+    Ok((states, Transitions::Table(table)))
+}
+
+/// Parses a `@TREE` section, returning the number of states and the tree.
+fn parse_tree(lines: &Lines) -> Result<(u8, Transitions), RuleFileError> {
+    let mut settings = [None; 3];
+    let mut levels: Vec<u32> = Vec::new();
+    let mut children: Vec<u32> = Vec::new();
+    let last_line = lines.last().map_or(0, |(line, _)| *line);
+
+    for &(line, text) in lines {
+        let names = ["num_states", "num_neighbors", "num_nodes"];
+        if let Some((index, value)) = names
+            .iter()
+            .enumerate()
+            .find_map(|(index, name)| Some((index, setting(text, name)?)))
+        {
+            let value = parse_number::<u32>(line, value)?;
+            // Checked right away, since the levels of the nodes are compared with it.
+            if index == 1 && value != 4 && value != 8 {
+                return Err(invalid(line, "num_neighbors must be 4 or 8"));
+            }
+            settings[index] = Some(value);
+            continue;
+        }
+        let [Some(states), Some(neighbors), Some(_)] = settings else {
+            return Err(invalid(
+                line,
+                "num_states, num_neighbors and num_nodes must come first",
+            ));
+        };
+        let numbers = text
+            .split_whitespace()
+            .map(|number| parse_number::<u32>(line, number))
+            .collect::<Result<Vec<u32>, _>>()?;
+        let (level, node_children) = numbers
+            .split_first()
+            .filter(|(_, children)| children.len() == states as usize)
+            .ok_or_else(|| invalid(line, format!("expected a level and {} children", states)))?;
+        let valid = match level {
+            0 => false,
+            1 => node_children.iter().all(|child| *child < states),
+            _ => node_children.iter().all(|child| {
+                levels
+                    .get(*child as usize)
+                    .is_some_and(|child_level| child_level + 1 == *level)
+            }),
+        };
+        if !valid || *level > neighbors + 1 {
+            return Err(invalid(line, "invalid node"));
+        }
+        levels.push(*level);
+        children.extend_from_slice(node_children);
+    }
+
+    let [Some(states), Some(neighbors), Some(nodes)] = settings else {
+        return Err(invalid(
+            last_line,
+            "missing num_states, num_neighbors or num_nodes",
+        ));
+    };
+    let states = u8::try_from(states)
+        .ok()
+        .filter(|states| *states >= 2)
+        .ok_or_else(|| invalid(last_line, "num_states must be between 2 and 255"))?;
+    let cells: &'static [usize] = if neighbors == 4 {
+        &[1, 3, 5, 7, 4]
+    } else {
+        &[0, 2, 6, 8, 1, 3, 5, 7, 4]
+    };
+    if levels.len() != nodes as usize || levels.last() != Some(&(neighbors + 1)) {
+        return Err(invalid(
+            last_line,
+            "the last of num_nodes nodes must be the root",
+        ));
+    }
+    let tree = Tree {
+        cells,
+        children,
+        root: nodes - 1,
+    };
+    Ok((states, Transitions::Tree(tree)))
+}
+
+/// Parses a `@COLORS` section, made of `state red green blue` lines, or of
+/// `red green blue red green blue` lines giving a gradient over the states other than `0`.
+fn parse_colors(lines: &Lines, states: u8) -> Result<Vec<Option<[u8; 3]>>, RuleFileError> {
+    let mut colors = vec![None; states as usize];
+    for &(line, text) in lines {
+        let numbers = text
+            .split([' ', '\t', ','])
+            .filter(|number| !number.is_empty())
+            .map(|number| parse_number::<u8>(line, number))
+            .collect::<Result<Vec<u8>, _>>()?;
+        match numbers[..] {
+            [state, red, green, blue] => {
+                if let Some(color) = colors.get_mut(state as usize) {
+                    *color = Some([red, green, blue]);
+                }
+            }
+            [r1, g1, b1, r2, g2, b2] => {
+                let steps = (states as i32 - 2).max(1);
+                for (state, color) in colors.iter_mut().enumerate().skip(1) {
+                    let interpolate = |from: u8, to: u8| {
+                        (from as i32 + (to as i32 - from as i32) * (state as i32 - 1) / steps) as u8
+                    };
+                    *color = Some([
+                        interpolate(r1, r2),
+                        interpolate(g1, g2),
+                        interpolate(b1, b2),
+                    ]);
+                }
+            }
+            _ => return Err(invalid(line, "expected a state and 3 color components")),
+        }
+    }
+    Ok(colors)
+}
+
+impl FromStr for RuleFile {
+    type Err = RuleFileError;
+
+    /// Parses the `@RULE`, `@TABLE` or `@TREE` and `@COLORS` sections, other sections are ignored.
+    /// Neighborhoods are Moore, von Neumann or hexagonal, see `Neighborhood`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut name = String::new();
+        let mut sections: Vec<(&str, Lines)> = Vec::new();
+        for (index, line) in s.lines().enumerate() {
+            let text = line.split('#').next().unwrap_or_default().trim();
+            if let Some(header) = text.strip_prefix('@') {
+                let (section, argument) = header.split_once(' ').unwrap_or((header, ""));
+                if section == "RULE" {
+                    argument.trim().clone_into(&mut name);
+                }
+                sections.push((section, Vec::new()));
+            } else if let Some((_, lines)) = sections.last_mut().filter(|_| !text.is_empty()) {
+                lines.push((index + 1, text));
+            }
+        }
+        let section = |name: &str| {
+            sections
+                .iter()
+                .find(|(section, _)| *section == name)
+                .map(|(_, lines)| lines)
+        };
+
+        let (states, transitions) = match (section("TABLE"), section("TREE")) {
+            (Some(table), _) => parse_table(table)?,
+            (None, Some(tree)) => parse_tree(tree)?,
+            (None, None) => return Err(RuleFileError::MissingTransitions),
+        };
+        let colors = match section("COLORS") {
+            Some(lines) => parse_colors(lines, states)?,
+            None => vec![None; states as usize],
+        };
+        Ok(Self {
+            name: if name.is_empty() {
+                "Unnamed".to_owned()
+            } else {
+                name
+            },
+            states,
+            transitions,
+            colors,
+        })
+    }
+}
+
+/// A pool of cells following a rule file, each cell storing its state.
+#[derive(Clone, Debug)]
+pub struct RuleFilePool {
+    /// State of each cell, indexed `[row][column]`.
+    state: Vec<Vec<u8>>,
+    /// Behavior of the edges.
+    topology: Topology,
+    rule: RuleFile,
+}
+
+impl RuleFilePool {
+    pub fn new(width: u32, height: u32, rule: RuleFile) -> Self {
+        Self {
+            state: vec![vec![0; width as usize]; height as usize],
+            topology: Topology::default(),
+            rule,
+        }
+    }
+
+    /// Creates a pool of the same size and topology, in which the alive cells of `pool` are in state `1`.
+    pub fn from_pool(pool: &Pool, rule: RuleFile) -> Self {
+        let mut result = Self::new(pool.width(), pool.height(), rule);
+        result.topology = pool.topology();
+        for (row, cells) in result.state.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                if pool.get_cell(row as u32, column as u32) {
+                    *cell = 1;
+                }
+            }
+        }
+        result
+    }
+
+    pub fn width(&self) -> u32 {
+        self.state.first().map_or(0, |row| row.len() as u32)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.state.len() as u32
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn rule(&self) -> &RuleFile {
+        &self.rule
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> u8 {
+        self.state[row as usize][column as usize]
+    }
+
+    pub fn set_cell(&mut self, row: u32, column: u32, state: u8) {
+        self.state[row as usize][column as usize] = state;
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.state.iter_mut().flatten() {
+            *cell = 0;
+        }
+    }
+
+    /// Computes the next state of the rows from `start` included to `stop` excluded.
+    fn next_state_rows(&self, start: u32, stop: u32) -> Vec<Vec<u8>> {
+        let (height, width) = (self.height(), self.width());
+        (start..stop)
+            .map(|row| {
+                (0..width)
+                    .map(|column| {
+                        let cells =
+                            self.topology
+                                .neighbor_states(height, width, row, column, |i, j| {
+                                    self.state[i as usize][j as usize]
+                                });
+                        self.rule.next_state(cells)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Computes the next generation, on several threads like `Pool::step`.
+    pub fn step(&mut self) {
+        self.state = compute_next_state(self.height(), self.width(), |start, stop| {
+            self.next_state_rows(start, stop)
+        });
+    }
+}
+
+impl StateGrid for RuleFilePool {
+    fn width(&self) -> u32 {
+        RuleFilePool::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        RuleFilePool::height(self)
+    }
+
+    fn state_count(&self) -> u8 {
+        self.rule.states()
+    }
+
+    fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.get_cell(row, column)
+    }
+
+    fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        self.set_cell(row, column, state)
+    }
+
+    fn step(&mut self) {
+        RuleFilePool::step(self)
+    }
+
+    fn clear(&mut self) {
+        RuleFilePool::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::rule::Rule;
+    use crate::wireworld::{Cell, WireWorld};
+
+    const WIREWORLD: &str = include_str!("../rules/WireWorld.rule");
+    const LIFE: &str = include_str!("../rules/Life.rule");
+
+    /// Returns the states of the neighborhood whose alive cells are the bits of `neighborhood`.
+    fn cells(neighborhood: u16) -> [u8; 9] {
+        core::array::from_fn(|bit| (neighborhood >> bit & 1) as u8)
+    }
+
+    #[test]
+    fn wireworld_table() {
+        let rule: RuleFile = WIREWORLD.parse().unwrap();
+        assert_eq!((rule.name(), rule.states()), ("WireWorld", 4));
+        assert_eq!(rule.color(1), Some([0, 128, 255]));
+        // Golly numbers the electron head 1, the tail 2 and the conductor 3.
+        let to_golly = |cell: Cell| match cell {
+            Cell::Empty => 0,
+            Cell::Head => 1,
+            Cell::Tail => 2,
+            Cell::Conductor => 3,
+        };
+        let mut circuit: WireWorld = ".tH.......\n#..#######\n.##.......".parse().unwrap();
+        let mut pool = RuleFilePool::new(circuit.width(), circuit.height(), rule);
+        for row in 0..circuit.height() {
+            for column in 0..circuit.width() {
+                pool.set_cell(row, column, to_golly(circuit.get_cell(row, column)));
+            }
+        }
+        for _ in 0..20 {
+            for row in 0..circuit.height() {
+                for column in 0..circuit.width() {
+                    assert_eq!(
+                        pool.get_cell(row, column),
+                        to_golly(circuit.get_cell(row, column))
+                    );
+                }
+            }
+            circuit.step();
+            pool.step();
+        }
+    }
+
+    #[test]
+    fn life_tree() {
+        let rule: RuleFile = LIFE.parse().unwrap();
+        assert_eq!((rule.name(), rule.states()), ("Life", 2));
+        assert_eq!(rule.color(0), Some([48, 48, 48]));
+        for neighborhood in 0..512 {
+            assert_eq!(
+                rule.next_state(cells(neighborhood)) == 1,
+                Rule::default().next_state_of(neighborhood)
+            );
+        }
+
+        let mut pool = Pool::new(200, 200);
+        pool += Pool::acorn().with_offset(96, 96);
+        let mut rule_pool = RuleFilePool::from_pool(&pool, rule);
+        for _ in 0..30 {
+            pool.step();
+            rule_pool.step();
+        }
+        let alive = |row, column| rule_pool.get_cell(row, column) == 1;
+        assert!(pool.alive_cells().all(|(row, column)| alive(row, column)));
+        assert_eq!(
+            rule_pool
+                .state
+                .iter()
+                .flatten()
+                .filter(|cell| **cell == 1)
+                .count() as u32,
+            pool.population()
+        );
+    }
+
+    #[test]
+    fn permute_table() {
+        let rule: RuleFile = "@RULE LifePermute
+            @TABLE
+            n_states:2
+            neighborhood:Moore
+            symmetries:permute
+            var a={0,1}
+            var b={a}
+            var c={a}
+            var d={a}
+            var e={a}
+            var f={a}
+            var g={a}
+            var h={a}
+            # Births, then survivals, then deaths.
+            0,1,1,1,0,0,0,0,0,1
+            1,1,1,0,0,0,0,0,0,1
+            1,1,1,1,0,0,0,0,0,1
+            1,a,b,c,d,e,f,g,h,0"
+            .parse()
+            .unwrap();
+        for neighborhood in 0..512 {
+            assert_eq!(
+                rule.next_state(cells(neighborhood)) == 1,
+                Rule::default().next_state_of(neighborhood)
+            );
+        }
+    }
+
+    #[test]
+    fn bound_variables() {
+        let rule: RuleFile = "@RULE Bound
+            @TABLE
+            n_states:3
+            neighborhood:vonNeumann
+            symmetries:rotate4
+            var a={1,2}
+            var b={0,1,2}
+            # Two adjacent neighbors in one state and the two others in another state.
+            0,a,a,b,b,a
+            # Without commas.
+            100002
+            @COLORS
+            0 0 0 0
+            255 0 0 0 0 255"
+            .parse()
+            .unwrap();
+        // Neighbors are given as [N, E, S, W], corners are ignored.
+        let next_state =
+            |center: u8, [n, e, s, w]: [u8; 4]| rule.next_state([1, n, 2, w, center, e, 2, s, 1]);
+        assert_eq!(next_state(0, [1, 1, 0, 0]), 1);
+        assert_eq!(next_state(0, [0, 2, 2, 0]), 2);
+        assert_eq!(next_state(0, [1, 2, 1, 2]), 0);
+        assert_eq!(next_state(0, [1, 1, 2, 0]), 0);
+        assert_eq!(next_state(0, [0, 0, 0, 0]), 0);
+        assert_eq!(next_state(1, [0, 0, 0, 0]), 2);
+        assert_eq!(next_state(1, [0, 1, 0, 0]), 1);
+        assert_eq!(rule.color(1), Some([255, 0, 0]));
+        assert_eq!(rule.color(2), Some([0, 0, 255]));
+    }
+
+    #[test]
+    fn invalid_files() {
+        let error = |text: &str| text.parse::<RuleFile>().unwrap_err().to_string();
+        assert_eq!(error("@RULE Empty"), "no @TABLE or @TREE section");
+        let table = "@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:none\n";
+        assert_eq!(
+            error(&format!("{}0,1,0,0,2,1", table)),
+            "invalid state \"2\" on line 5"
+        );
+        assert_eq!(
+            error(&format!("{}0,1,0,0,1", table)),
+            "expected 6 states per transition on line 5"
+        );
+        assert_eq!(
+            error(&format!("{}var a={{0,1}}\n0,a,0,0,0,b", table)),
+            "invalid state \"b\" on line 6"
+        );
+        assert_eq!(
+            error(&format!("{}var a={{0,1}}\n0,1,0,0,0,a", table)),
+            "the new state is a variable not used before on line 6"
+        );
+        assert_eq!(
+            error("@TABLE\nn_states:2\n0,1,0,0,0,1"),
+            "n_states, neighborhood and symmetries must come first on line 3"
+        );
+        assert_eq!(
+            error("@TREE\nnum_states=2\nnum_neighbors=4\nnum_nodes=2\n1 0 1\n3 0 0"),
+            "invalid node on line 6"
+        );
+        assert_eq!(
+            error("@TREE\nnum_states=2\nnum_neighbors=4294967295\nnum_nodes=1\n1 0 1"),
+            "num_neighbors must be 4 or 8 on line 3"
+        );
+    }
+}
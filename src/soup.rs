@@ -0,0 +1,181 @@
+use crate::census::{self, UNKNOWN_OBJECT};
+use crate::grid::Grid;
+use crate::pool::{Area, Pool, RandomFill, Topology};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Side of the random square placed at the center of the universe.
+pub const SOUP_SIZE: u32 = 16;
+/// Side of the toroidal universe in which soups evolve.
+const UNIVERSE_SIZE: u32 = 64;
+/// Soups still evolving after this many generations are reported as unstabilized.
+const MAX_GENERATIONS: u32 = 10_000;
+
+/// Runs the soup generated from the given seed until it becomes periodic
+/// and returns the apgcode of every resulting object,
+/// or `None` if it did not stabilize within `MAX_GENERATIONS`.
+pub fn run_soup(seed: u64) -> Option<Vec<String>> {
+    let mut universe = Pool::new(UNIVERSE_SIZE, UNIVERSE_SIZE);
+    universe.set_topology(Topology::Torus);
+    let offset = (UNIVERSE_SIZE - SOUP_SIZE) / 2;
+    universe.randomize_with(&RandomFill::new(seed, 0.5).with_area(Area {
+        row: offset,
+        column: offset,
+        height: SOUP_SIZE,
+        width: SOUP_SIZE,
+    }));
+
+    // Alive cells of the states seen so far, grouped by hash.
+    let mut seen_states: HashMap<u64, Vec<Vec<(u32, u32)>>> = HashMap::new();
+    for _ in 0..MAX_GENERATIONS {
+        let cells: Vec<(u32, u32)> = universe.alive_cells().collect();
+        let mut hasher = DefaultHasher::new();
+        cells.hash(&mut hasher);
+        // States with the same hash are compared, so that a collision is not taken for a period.
+        let states = seen_states.entry(hasher.finish()).or_default();
+        if states.contains(&cells) {
+            return Some(census::census(&universe));
+        }
+        states.push(cells);
+        universe.step();
+    }
+    None
+}
+
+/// Search for objects in many random soups, in the style of apgsearch.
+///
+/// The soup number `i` of a search is generated from the seed `seed + i`,
+/// so that any soup can be replayed from the seed reported alongside it.
+pub struct SoupSearch {
+    seed: u64,
+    soup_count: u64,
+    /// Number of occurrences of each object.
+    counts: HashMap<String, u64>,
+    /// First few soup seeds in which each object appeared.
+    samples: HashMap<String, Vec<u64>>,
+    /// Seeds of the soups which did not stabilize.
+    unstabilized: Vec<u64>,
+}
+
+impl SoupSearch {
+    /// Objects seen at most this many times are reported with sample seeds.
+    const RARE_COUNT: u64 = 10;
+    const MAX_SAMPLES: usize = 5;
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            soup_count: 0,
+            counts: HashMap::new(),
+            samples: HashMap::new(),
+            unstabilized: Vec::new(),
+        }
+    }
+
+    /// Runs the given number of additional soups.
+    pub fn run(&mut self, soups: u64) {
+        for _ in 0..soups {
+            let soup_seed = self.seed.wrapping_add(self.soup_count);
+            self.soup_count += 1;
+            match run_soup(soup_seed) {
+                Some(objects) => {
+                    for object in objects {
+                        let samples = self.samples.entry(object.clone()).or_default();
+                        if samples.len() < Self::MAX_SAMPLES && !samples.contains(&soup_seed) {
+                            samples.push(soup_seed);
+                        }
+                        *self.counts.entry(object).or_default() += 1;
+                    }
+                }
+                None => self.unstabilized.push(soup_seed),
+            }
+        }
+    }
+
+    pub fn soup_count(&self) -> u64 {
+        self.soup_count
+    }
+
+    pub fn counts(&self) -> &HashMap<String, u64> {
+        &self.counts
+    }
+
+    /// Writes the object counts, most common first, followed by sample seeds of rare objects.
+    pub fn write_report<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let object_count: u64 = self.counts.values().sum();
+        writeln!(file, "# Soup search")?;
+        writeln!(file, "seed: {}", self.seed)?;
+        writeln!(file, "soup size: {}x{}", SOUP_SIZE, SOUP_SIZE)?;
+        writeln!(file, "soups: {}", self.soup_count)?;
+        writeln!(file, "objects: {}", object_count)?;
+
+        let mut counts: Vec<(&String, &u64)> = self.counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        writeln!(file)?;
+        writeln!(file, "# Census")?;
+        for (object, count) in &counts {
+            writeln!(file, "{} {}", object, count)?;
+        }
+
+        let rare: BTreeMap<&String, &Vec<u64>> = self
+            .samples
+            .iter()
+            .filter(|(object, _)| {
+                self.counts[*object] <= Self::RARE_COUNT && object.as_str() != UNKNOWN_OBJECT
+            })
+            .collect();
+        writeln!(file)?;
+        writeln!(file, "# Sample soups of rare objects")?;
+        for (object, seeds) in rare {
+            let seeds: Vec<String> = seeds.iter().map(|seed| seed.to_string()).collect();
+            writeln!(file, "{} {}", object, seeds.join(" "))?;
+        }
+
+        writeln!(file)?;
+        writeln!(file, "# Unstabilized soups")?;
+        for seed in &self.unstabilized {
+            writeln!(file, "{}", seed)?;
+        }
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_seed_census() {
+        let mut search = SoupSearch::new(7);
+        search.run(2);
+        assert_eq!(search.soup_count(), 2);
+        let mut objects = run_soup(7).unwrap();
+        objects.sort();
+        assert_eq!(
+            objects,
+            ["xq4_153", "xs4_33", "xs4_33", "xs4_33", "xs4_33", "xs4_33", "xs6_696"]
+        );
+        let expected = [
+            ("xs4_33", 10),
+            ("xs6_696", 7),
+            ("xp2_7", 7),
+            ("xs5_253", 4),
+            ("xs7_25ac", 2),
+            ("xs7_2596", 2),
+            ("xq4_153", 1),
+            ("xs4_252", 1),
+        ];
+        assert_eq!(
+            *search.counts(),
+            expected
+                .into_iter()
+                .map(|(object, count)| (object.to_owned(), count))
+                .collect()
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::grid::Grid;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Activity of the pool during one generation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    /// Number of alive cells after the step.
+    pub population: u32,
+    /// Number of cells which became alive during the step.
+    pub births: u32,
+    /// Number of cells which died during the step.
+    pub deaths: u32,
+    /// Width and height of the smallest rectangle containing every alive cell.
+    pub bounding_box: (u32, u32),
+}
+
+/// Time series of the activity of a pool, one record per generation.
+/// Only the most recent records are kept, up to a maximum count.
+pub struct Statistics {
+    records: VecDeque<GenerationStats>,
+    max_records: usize,
+}
+
+impl Statistics {
+    pub fn new(max_records: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            max_records,
+        }
+    }
+
+    /// Records the step which turned `previous` into `current`, the given generation.
+    pub fn record<G: Grid>(&mut self, generation: u64, previous: &G, current: &G) {
+        let mut births = 0;
+        let mut deaths = 0;
+        for row in 0..current.height().min(previous.height()) {
+            for column in 0..current.width().min(previous.width()) {
+                match (
+                    previous.get_cell(row, column),
+                    current.get_cell(row, column),
+                ) {
+                    (false, true) => births += 1,
+                    (true, false) => deaths += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        if self.records.len() == self.max_records {
+            self.records.pop_front();
+        }
+        self.records.push_back(GenerationStats {
+            generation,
+            population: current.population(),
+            births,
+            deaths,
+            bounding_box: current
+                .bounding_box()
+                .map_or((0, 0), |area| (area.width, area.height)),
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    pub fn records(&self) -> &VecDeque<GenerationStats> {
+        &self.records
+    }
+
+    /// Returns the most recent records, at most `count` of them, oldest first.
+    pub fn last(&self, count: usize) -> impl Iterator<Item = &GenerationStats> {
+        self.records
+            .iter()
+            .skip(self.records.len().saturating_sub(count))
+    }
+
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "generation,population,births,deaths,bounding_box_width,bounding_box_height"
+        )?;
+        for record in &self.records {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                record.generation,
+                record.population,
+                record.births,
+                record.deaths,
+                record.bounding_box.0,
+                record.bounding_box.1
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::Pool;
+
+    fn blinker() -> Pool {
+        let mut pool = Pool::new(5, 5);
+        pool.set_cell(2, 1, true);
+        pool.set_cell(2, 2, true);
+        pool.set_cell(2, 3, true);
+        pool
+    }
+
+    #[test]
+    fn record() {
+        let mut statistics = Statistics::new(10);
+        let mut pool = blinker();
+        let previous = pool.clone();
+        pool.step();
+        statistics.record(1, &previous, &pool);
+        assert_eq!(
+            statistics.records()[0],
+            GenerationStats {
+                generation: 1,
+                population: 3,
+                births: 2,
+                deaths: 2,
+                bounding_box: (1, 3),
+            }
+        );
+    }
+
+    #[test]
+    fn record_keeps_most_recent() {
+        let mut statistics = Statistics::new(3);
+        let mut pool = blinker();
+        for generation in 1..=5 {
+            let previous = pool.clone();
+            pool.step();
+            statistics.record(generation, &previous, &pool);
+        }
+        let generations: Vec<u64> = statistics.records().iter().map(|r| r.generation).collect();
+        assert_eq!(generations, vec![3, 4, 5]);
+        let last: Vec<u64> = statistics.last(2).map(|r| r.generation).collect();
+        assert_eq!(last, vec![4, 5]);
+    }
+
+    #[test]
+    fn write_csv() {
+        let mut statistics = Statistics::new(10);
+        let mut pool = blinker();
+        let previous = pool.clone();
+        pool.step();
+        statistics.record(1, &previous, &pool);
+
+        let mut csv = Vec::new();
+        statistics.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "generation,population,births,deaths,bounding_box_width,bounding_box_height\n\
+             1,3,2,2,1,3\n"
+        );
+    }
+}
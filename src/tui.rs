@@ -0,0 +1,411 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, style, terminal};
+use game_of_life::grid::Grid;
+use game_of_life::pool::{Pool, RandomFill, Symmetry};
+use game_of_life::rewind::Timeline;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Characters used to draw the cells, several cells being packed in each character.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Glyphs {
+    /// `▀`, `▄` and `█`, two cells per character, one above the other.
+    #[default]
+    HalfBlock,
+    /// Braille patterns, eight cells per character, four rows of two.
+    Braille,
+}
+impl FromStr for Glyphs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "half-block" => Ok(Glyphs::HalfBlock),
+            "braille" => Ok(Glyphs::Braille),
+            _ => Err(format!(
+                "invalid glyphs \"{}\", expected half-block or braille",
+                s
+            )),
+        }
+    }
+}
+
+impl Glyphs {
+    /// Returns the number of `(rows, columns)` of cells drawn by one character.
+    pub fn cells_per_character(self) -> (u32, u32) {
+        match self {
+            Glyphs::HalfBlock => (2, 1),
+            Glyphs::Braille => (4, 2),
+        }
+    }
+
+    /// Returns the character drawing a block of cells, `alive(row, column)` being relative to the block.
+    fn character(self, alive: impl Fn(u32, u32) -> bool) -> char {
+        match self {
+            Glyphs::HalfBlock => match (alive(0, 0), alive(1, 0)) {
+                (false, false) => ' ',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (true, true) => '█',
+            },
+            Glyphs::Braille => {
+                // Bit of each dot of a braille pattern, indexed [row][column].
+                const DOTS: [[u32; 2]; 4] =
+                    [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+                let mut pattern = 0;
+                for (row, dots) in DOTS.iter().enumerate() {
+                    for (column, dot) in dots.iter().enumerate() {
+                        if alive(row as u32, column as u32) {
+                            pattern |= dot;
+                        }
+                    }
+                }
+                char::from_u32(0x2800 + pattern).unwrap()
+            }
+        }
+    }
+
+    /// Draws the cells of the pool starting at `(first_row, first_column)`, on at most
+    /// `lines` lines of `columns` characters. Cells outside of the pool are dead.
+    pub fn draw<G: Grid>(
+        self,
+        pool: &G,
+        (first_row, first_column): (i64, i64),
+        lines: u16,
+        columns: u16,
+    ) -> Vec<String> {
+        let (block_height, block_width) = self.cells_per_character();
+        let alive = |row: i64, column: i64| {
+            pool.contains(row, column) && pool.get_cell(row as u32, column as u32)
+        };
+        (0..lines as i64)
+            .map(|line| {
+                (0..columns as i64)
+                    .map(|character| {
+                        let row = first_row + line * block_height as i64;
+                        let column = first_column + character * block_width as i64;
+                        self.character(|r, c| alive(row + r as i64, column + c as i64))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Raw mode and alternate screen, left when dropped, even on errors or panics.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        // Created before entering the alternate screen, so that a failure there is undone too.
+        let guard = TerminalGuard;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // Every step is attempted, the terminal is as restored as it can be.
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Interactive game in the terminal, with the keys of the window.
+pub struct Tui {
+    pool: Pool,
+    random_fill: RandomFill,
+    glyphs: Glyphs,
+    paused: bool,
+    percent_speed: u8,
+    render_help: bool,
+    generation: u64,
+    timeline: Timeline,
+    /// Cell drawn at the top left corner of the terminal.
+    origin: (i64, i64),
+    quit: bool,
+}
+
+impl Tui {
+    const MAX_UPS: u64 = 165;
+    const SPEED_STEP: u8 = 10;
+    const DENSITY_STEP: f64 = 0.05;
+    const MAX_TIMELINE_GENERATIONS: usize = 1000;
+    /// Number of characters moved by a pan.
+    const PAN_STEP: i64 = 8;
+
+    pub fn new(pool: Pool, random_fill: RandomFill, glyphs: Glyphs) -> Self {
+        Self {
+            pool,
+            random_fill,
+            glyphs,
+            paused: false,
+            percent_speed: 10,
+            render_help: true,
+            generation: 0,
+            timeline: Timeline::new(Self::MAX_TIMELINE_GENERATIONS, 0),
+            origin: (0, 0),
+            quit: false,
+        }
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn set_percent_speed(&mut self, percent_speed: u8) {
+        self.percent_speed = percent_speed.clamp(1, 100);
+    }
+
+    /// Takes over the terminal until Q or Esc is pressed.
+    pub fn run(&mut self) -> io::Result<()> {
+        let _guard = TerminalGuard::new()?;
+        self.event_loop(&mut io::stdout())
+    }
+
+    fn event_loop(&mut self, stdout: &mut io::Stdout) -> io::Result<()> {
+        self.center(terminal::size()?);
+        let mut next_step = Instant::now();
+        while !self.quit {
+            self.render(stdout)?;
+            let step_duration = Duration::from_secs(1)
+                .div_f64((Self::MAX_UPS * self.percent_speed as u64) as f64 / 100.0);
+            let timeout = if self.paused {
+                Duration::from_secs(1)
+            } else {
+                next_step.saturating_duration_since(Instant::now())
+            };
+            if event::poll(timeout)? {
+                match event::read()? {
+                    Event::Key(key) if key.kind != KeyEventKind::Release => self.process_key(key),
+                    Event::Resize(columns, lines) => self.center((columns, lines)),
+                    _ => {}
+                }
+            }
+            // Stepping on time rather than on timeouts keeps running while a key is held.
+            if !self.paused && Instant::now() >= next_step {
+                self.step();
+                next_step = Instant::now() + step_duration;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of `(columns, lines)` of the terminal used by the pool.
+    fn view_size(&self, (columns, lines): (u16, u16)) -> (u16, u16) {
+        (
+            columns,
+            lines.saturating_sub(1 + self.help_lines().len() as u16),
+        )
+    }
+
+    /// Centers the pool in a terminal of the given size.
+    fn center(&mut self, terminal_size: (u16, u16)) {
+        let (columns, lines) = self.view_size(terminal_size);
+        let (block_height, block_width) = self.glyphs.cells_per_character();
+        self.origin = (
+            (self.pool.height() as i64 - (lines as u32 * block_height) as i64) / 2,
+            (self.pool.width() as i64 - (columns as u32 * block_width) as i64) / 2,
+        );
+    }
+
+    fn help_lines(&self) -> Vec<String> {
+        if !self.render_help {
+            return vec![String::from("H : Show help")];
+        }
+        vec![
+            format!(
+                "Space : {} | , . : Step back / forward | ← → : Speed : {}% | Shift+arrows : Pan",
+                if self.paused { "Resume" } else { "Pause" },
+                self.percent_speed
+            ),
+            format!(
+                "R : Randomize | E : Replay seed {} | ↑ ↓ : Density : {:.2} | S : Symmetry : {}",
+                self.random_fill.seed, self.random_fill.density, self.random_fill.symmetry
+            ),
+            String::from("Del : Clear | H : Hide help | Q Esc : Quit"),
+        ]
+    }
+
+    fn render(&self, stdout: &mut io::Stdout) -> io::Result<()> {
+        let terminal_size = terminal::size()?;
+        let (columns, lines) = self.view_size(terminal_size);
+        let status = format!(
+            "Generation {} | Population {} | {}x{} {} {:?}{}",
+            self.generation,
+            self.pool.population(),
+            self.pool.width(),
+            self.pool.height(),
+            self.pool.rule(),
+            self.pool.topology(),
+            if self.paused { " | Paused" } else { "" }
+        );
+        let mut frame = vec![status];
+        frame.extend(self.glyphs.draw(&self.pool, self.origin, lines, columns));
+        frame.extend(self.help_lines());
+
+        for (line, text) in frame.iter().enumerate() {
+            let text: String = text.chars().take(terminal_size.0 as usize).collect();
+            queue!(
+                stdout,
+                cursor::MoveTo(0, line as u16),
+                style::Print(text),
+                terminal::Clear(terminal::ClearType::UntilNewLine)
+            )?;
+        }
+        stdout.flush()
+    }
+
+    fn step(&mut self) {
+        let previous = self.pool.clone();
+        if !self.timeline.forward(&mut self.pool) {
+            self.pool.step();
+            self.timeline.record(&previous, &self.pool);
+        }
+        self.generation += 1;
+    }
+
+    fn step_back(&mut self) {
+        if self.timeline.back(&mut self.pool) {
+            self.generation -= 1;
+        }
+    }
+
+    /// The pool was modified outside of a step.
+    fn restart(&mut self) {
+        self.generation = 0;
+        self.timeline.reset(0);
+    }
+
+    fn process_key(&mut self, key: KeyEvent) {
+        let (block_height, block_width) = self.glyphs.cells_per_character();
+        match key.code {
+            // Shift+arrows : pan
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.origin.0 -= Self::PAN_STEP * block_height as i64
+            }
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.origin.0 += Self::PAN_STEP * block_height as i64
+            }
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.origin.1 -= Self::PAN_STEP * block_width as i64
+            }
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.origin.1 += Self::PAN_STEP * block_width as i64
+            }
+            // Q / Esc / Ctrl+C : quit
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            // Space : Pause / Resume
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            // , / . : step backward / forward, pausing the simulation
+            KeyCode::Char(',') => {
+                self.paused = true;
+                self.step_back();
+            }
+            KeyCode::Char('.') => {
+                self.paused = true;
+                self.step();
+            }
+            // Del : Clear pool
+            KeyCode::Delete => {
+                self.pool.clear();
+                self.restart();
+            }
+            // R : Randomize pool with a new seed
+            KeyCode::Char('r') => {
+                self.random_fill.seed = rand::random();
+                self.pool.randomize_with(&self.random_fill);
+                self.restart();
+            }
+            // E : Replay the last randomization
+            KeyCode::Char('e') => {
+                self.pool.randomize_with(&self.random_fill);
+                self.restart();
+            }
+            // S : cycle through symmetries of the next randomization
+            KeyCode::Char('s') => {
+                let current = Symmetry::ALL
+                    .iter()
+                    .position(|symmetry| *symmetry == self.random_fill.symmetry)
+                    .unwrap_or(0);
+                self.random_fill.symmetry = Symmetry::ALL[(current + 1) % Symmetry::ALL.len()];
+            }
+            // Up / Down : modify density of the next randomization
+            KeyCode::Up => {
+                self.random_fill.density = (self.random_fill.density + Self::DENSITY_STEP).min(1.0)
+            }
+            KeyCode::Down => {
+                self.random_fill.density = (self.random_fill.density - Self::DENSITY_STEP).max(0.0)
+            }
+            // H : toggle help
+            KeyCode::Char('h') => self.render_help = !self.render_help,
+            // Right / Left : modify speed, 1% being below 10%
+            KeyCode::Left => {
+                if self.percent_speed == 10 {
+                    self.percent_speed = 1;
+                } else if self.percent_speed > 10 {
+                    self.percent_speed -= Self::SPEED_STEP;
+                }
+            }
+            KeyCode::Right => {
+                if self.percent_speed == 1 {
+                    self.percent_speed = 10;
+                } else if self.percent_speed < 100 {
+                    self.percent_speed += Self::SPEED_STEP;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_half_blocks() {
+        let glider = Pool::glider_south_east();
+        assert_eq!(
+            Glyphs::HalfBlock.draw(&glider, (0, 0), 2, 3),
+            vec![" ▀▄", "▀▀▀"]
+        );
+        assert_eq!(
+            Glyphs::HalfBlock.draw(&glider, (-1, -1), 2, 4),
+            vec!["  ▄ ", " ▄▄█"]
+        );
+    }
+
+    #[test]
+    fn draw_braille() {
+        let glider = Pool::glider_south_east();
+        assert_eq!(Glyphs::Braille.draw(&glider, (0, 0), 1, 2), vec!["⠬⠆"]);
+        let mut full = Pool::new(2, 4);
+        for row in 0..4 {
+            full.set_cell(row, 0, true);
+            full.set_cell(row, 1, true);
+        }
+        assert_eq!(Glyphs::Braille.draw(&full, (0, 0), 1, 1), vec!["⣿"]);
+    }
+
+    #[test]
+    fn step_back_and_forward() {
+        let mut pool = Pool::new(20, 20);
+        pool += Pool::acorn().with_offset(8, 6);
+        let mut tui = Tui::new(pool.clone(), RandomFill::default(), Glyphs::Braille);
+        for _ in 0..5 {
+            tui.process_key(KeyEvent::from(KeyCode::Char('.')));
+        }
+        assert!(tui.paused);
+        assert_eq!(tui.generation, 5);
+        for _ in 0..6 {
+            tui.process_key(KeyEvent::from(KeyCode::Char(',')));
+        }
+        assert_eq!(tui.generation, 0);
+        assert_eq!(tui.pool, pool);
+    }
+}
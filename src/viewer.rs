@@ -0,0 +1,342 @@
+use graphics::types::Color;
+use graphics::Context;
+use piston::{Key, MouseButton};
+use piston_window::G2d;
+
+use crate::viewport::Viewport;
+use crate::window::{Frame, Scene};
+use game_of_life::grid::StateGrid;
+use game_of_life::rule_file::RuleFile;
+
+/// Colors of the states of a Generations rule : white when dead, black when alive,
+/// then from red to pale yellow as cells decay.
+pub fn decay_palette(states: u8) -> Vec<Color> {
+    const DEAD_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+    const ALIVE_COLOR: Color = [0.0, 0.0, 0.0, 1.0];
+    const FIRST_DYING_COLOR: Color = [0.8, 0.0, 0.0, 1.0];
+    const LAST_DYING_COLOR: Color = [1.0, 0.95, 0.6, 1.0];
+
+    let dying_states = states.saturating_sub(2);
+    let dying_colors = (0..dying_states).map(|index| {
+        let t = if dying_states > 1 {
+            index as f32 / (dying_states - 1) as f32
+        } else {
+            0.0
+        };
+        core::array::from_fn(|channel| {
+            FIRST_DYING_COLOR[channel] * (1.0 - t) + LAST_DYING_COLOR[channel] * t
+        })
+    });
+    [DEAD_COLOR, ALIVE_COLOR]
+        .into_iter()
+        .chain(dying_colors)
+        .collect()
+}
+
+/// Colors of the states of a rule file, given by its `@COLORS` section,
+/// the states it does not give being colored like `decay_palette`.
+pub fn rule_file_palette(rule: &RuleFile) -> Vec<Color> {
+    let mut palette = decay_palette(rule.states());
+    for (state, color) in palette.iter_mut().enumerate() {
+        if let Some(rgb) = rule.color(state as u8) {
+            *color = [
+                rgb[0] as f32 / 255.0,
+                rgb[1] as f32 / 255.0,
+                rgb[2] as f32 / 255.0,
+                1.0,
+            ];
+        }
+    }
+    palette
+}
+
+/// Colors evenly spread along the gradient through `stops`, one per level.
+fn gradient_palette(stops: &[Color], levels: u8) -> Vec<Color> {
+    let last_stop = (stops.len() - 1) as f32;
+    (0..levels)
+        .map(|level| {
+            let position = level as f32 / levels.saturating_sub(1).max(1) as f32 * last_stop;
+            let index = (position as usize).min(stops.len() - 2);
+            let t = position - index as f32;
+            core::array::from_fn(|channel| {
+                stops[index][channel] * (1.0 - t) + stops[index + 1][channel] * t
+            })
+        })
+        .collect()
+}
+
+/// Colors of continuous values, from dark purple through blue and green to yellow, like viridis.
+pub fn colormap_palette(levels: u8) -> Vec<Color> {
+    const STOPS: [Color; 5] = [
+        [0.267, 0.005, 0.329, 1.0],
+        [0.230, 0.322, 0.546, 1.0],
+        [0.128, 0.567, 0.551, 1.0],
+        [0.369, 0.789, 0.383, 1.0],
+        [0.993, 0.906, 0.144, 1.0],
+    ];
+    gradient_palette(&STOPS, levels)
+}
+
+/// Colors of continuous values, from black to white.
+pub fn grayscale_palette(levels: u8) -> Vec<Color> {
+    gradient_palette(&[[0.0, 0.0, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0]], levels)
+}
+
+/// Colors of the WireWorld states : empty, conductor, electron head and electron tail.
+pub const WIREWORLD_PALETTE: [Color; 4] = [
+    [0.0, 0.0, 0.0, 1.0],
+    [1.0, 0.75, 0.0, 1.0],
+    [0.2, 0.4, 1.0, 1.0],
+    [1.0, 0.2, 0.1, 1.0],
+];
+
+/// File written with `E`, and the function giving its content.
+type Export<G> = (&'static str, fn(&G) -> String);
+
+/// Window content showing a grid with more than two states, each state drawn with its color.
+pub struct Viewer<G: StateGrid> {
+    grid: G,
+    /// Color of each state, state `0` being the background.
+    palette: Vec<Color>,
+    /// Palette swapped with `palette` with `C`.
+    alternative_palette: Option<Vec<Color>>,
+    /// Names of the states shown in the help, their numbers if empty.
+    state_names: Vec<&'static str>,
+    /// State painted with the left button.
+    selected_state: u8,
+    /// Number of steps since the grid was last cleared.
+    generation: u64,
+    export: Option<Export<G>>,
+}
+
+impl<G: StateGrid> Viewer<G> {
+    /// Shows the given grid, `palette` having a color for each state.
+    /// With more than ten states, the last one is painted at first.
+    pub fn new(grid: G, palette: Vec<Color>) -> Self {
+        let selected_state = match grid.state_count() {
+            count if count > 10 => count - 1,
+            _ => 1,
+        };
+        Self {
+            grid,
+            palette,
+            alternative_palette: None,
+            state_names: Vec::new(),
+            selected_state,
+            generation: 0,
+            export: None,
+        }
+    }
+
+    /// Names the states, so that the painting tools show what they paint.
+    pub fn with_state_names(mut self, state_names: &[&'static str]) -> Self {
+        self.state_names = state_names.to_vec();
+        self
+    }
+
+    /// Gives a second palette, swapped with the first one with `C`.
+    pub fn with_alternative_palette(mut self, palette: Vec<Color>) -> Self {
+        self.alternative_palette = Some(palette);
+        self
+    }
+
+    /// Exports the grid to the given file with `E`, `to_text` giving the content of the file.
+    pub fn with_export(mut self, path: &'static str, to_text: fn(&G) -> String) -> Self {
+        self.export = Some((path, to_text));
+        self
+    }
+
+    /// Returns the color of a state, black if the palette is too short.
+    fn state_color(palette: &[Color], state: u8) -> Color {
+        palette
+            .get(state as usize)
+            .copied()
+            .unwrap_or([0.0, 0.0, 0.0, 1.0])
+    }
+
+    /// Selects the state painted with the left button with a digit key, if the grid has it.
+    /// With more than ten states, the digits are spread up to the last state.
+    fn select_state(&mut self, digit: u8) {
+        let state_count = self.grid.state_count();
+        let state = if state_count > 10 {
+            (digit as u32 * (state_count - 1) as u32 / 9) as u8
+        } else {
+            digit
+        };
+        if state < state_count {
+            self.selected_state = state;
+        }
+    }
+}
+
+impl<G: StateGrid> Scene for Viewer<G> {
+    fn width(&self) -> u32 {
+        self.grid.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.grid.height()
+    }
+
+    fn step(&mut self, _frame: &mut Frame) {
+        self.grid.step();
+        self.generation += 1;
+    }
+
+    fn draw(&self, viewport: &Viewport, _cursor: [f64; 2], c: Context, g: &mut G2d) {
+        use graphics::*;
+
+        // Draw the grid area, then a square for each visible cell which is not in the background state
+        let [grid_x, grid_y] = viewport.cell_to_screen(0, 0);
+        let [grid_end_x, grid_end_y] =
+            viewport.cell_to_screen(self.grid.height() as i64, self.grid.width() as i64);
+        rectangle(
+            Self::state_color(&self.palette, 0),
+            [grid_x, grid_y, grid_end_x - grid_x, grid_end_y - grid_y],
+            c.transform,
+            g,
+        );
+        let cell_size = viewport.cell_size();
+        let (rows, columns) =
+            viewport.visible_cells(c.get_view_size(), self.grid.height(), self.grid.width());
+        for i in rows {
+            for j in columns.clone() {
+                let state = self.grid.get_state(i, j);
+                if state != 0 {
+                    let [x, y] = viewport.cell_to_screen(i as i64, j as i64);
+                    rectangle(
+                        Self::state_color(&self.palette, state),
+                        [x, y, cell_size, cell_size],
+                        c.transform,
+                        g,
+                    );
+                }
+            }
+        }
+    }
+
+    fn help_lines(&self) -> Vec<String> {
+        [
+            format!("Generation : {}", self.generation),
+            ". : step".to_owned(),
+            format!(
+                "1-{} : select state : {}",
+                self.grid.state_count().clamp(2, 10) - 1,
+                match self.state_names.get(self.selected_state as usize) {
+                    Some(name) => name.to_string(),
+                    None => self.selected_state.to_string(),
+                }
+            ),
+            "Left click : paint state".to_owned(),
+            "Right click : erase cell".to_owned(),
+            "del : clear screen".to_owned(),
+        ]
+        .into_iter()
+        .chain(
+            self.export
+                .map(|(path, _)| format!("E : export to {}", path)),
+        )
+        .chain(
+            self.alternative_palette
+                .is_some()
+                .then(|| "C : switch colors".to_owned()),
+        )
+        .collect()
+    }
+
+    fn key_press(&mut self, frame: &mut Frame, key: Key) -> bool {
+        match key {
+            // . : step forward, pausing the simulation
+            Key::Period => {
+                frame.set_paused(true);
+                self.step(frame);
+            }
+            // Del : Clear grid
+            Key::Delete => {
+                self.grid.clear();
+                self.generation = 0;
+            }
+            // E : export the grid, if it can be
+            Key::E => {
+                if let Some((path, to_text)) = self.export {
+                    frame.show_message(match std::fs::write(path, to_text(&self.grid)) {
+                        Ok(()) => format!("Exported to {}", path),
+                        Err(error) => format!("Cannot export to {} : {}", path, error),
+                    });
+                }
+            }
+            // C : switch to the alternative palette, if there is one
+            Key::C => {
+                if let Some(palette) = &mut self.alternative_palette {
+                    std::mem::swap(&mut self.palette, palette);
+                }
+            }
+            // 1-9 : select the painted state
+            Key::D1 | Key::NumPad1 => self.select_state(1),
+            Key::D2 | Key::NumPad2 => self.select_state(2),
+            Key::D3 | Key::NumPad3 => self.select_state(3),
+            Key::D4 | Key::NumPad4 => self.select_state(4),
+            Key::D5 | Key::NumPad5 => self.select_state(5),
+            Key::D6 | Key::NumPad6 => self.select_state(6),
+            Key::D7 | Key::NumPad7 => self.select_state(7),
+            Key::D8 | Key::NumPad8 => self.select_state(8),
+            Key::D9 | Key::NumPad9 => self.select_state(9),
+            // Other keys are handled by the frame
+            _ => return false,
+        }
+        true
+    }
+
+    /// Paints or erases cells depending on the button pressed on the mouse.
+    fn mouse_held(&mut self, frame: &mut Frame, button: MouseButton) {
+        let Some((row, column)) = frame.cursor_cell(self.grid.height(), self.grid.width()) else {
+            return;
+        };
+        match button {
+            MouseButton::Left => self.grid.set_state(row, column, self.selected_state),
+            MouseButton::Right => self.grid.set_state(row, column, 0),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decay_colors() {
+        let palette = decay_palette(5);
+        assert_eq!(palette.len(), 5);
+        assert_eq!(palette[0], [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(palette[1], [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(palette[2], [0.8, 0.0, 0.0, 1.0]);
+        assert_eq!(palette[4], [1.0, 0.95, 0.6, 1.0]);
+        assert_eq!(decay_palette(2).len(), 2);
+    }
+
+    #[test]
+    fn rule_file_colors() {
+        let rule: RuleFile =
+            "@TABLE\nn_states:3\nneighborhood:Moore\nsymmetries:none\n@COLORS\n1 255 0 51"
+                .parse()
+                .unwrap();
+        let palette = rule_file_palette(&rule);
+        assert_eq!(palette[0], [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(palette[1], [1.0, 0.0, 0.2, 1.0]);
+        assert_eq!(palette[2], [0.8, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn continuous_colors() {
+        let palette = grayscale_palette(5);
+        assert_eq!(palette.len(), 5);
+        assert_eq!(palette[0], [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(palette[2], [0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(palette[4], [1.0, 1.0, 1.0, 1.0]);
+        let palette = colormap_palette(255);
+        assert_eq!(palette.len(), 255);
+        assert_eq!(palette[0], [0.267, 0.005, 0.329, 1.0]);
+        assert_eq!(palette[254], [0.993, 0.906, 0.144, 1.0]);
+    }
+}
@@ -0,0 +1,232 @@
+use game_of_life::pool::Area;
+use std::ops::Range;
+
+/// Conversion between cell coordinates and screen positions, under zoom and pan.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Size of a cell on screen, in pixels.
+    cell_size: f64,
+    /// Screen position `[x, y]` of the top left corner of the cell (0, 0).
+    origin: [f64; 2],
+    /// Horizontal shift of each row relative to the previous one, in cells.
+    /// Shifting rows by half a cell lays the cells out like hexagons.
+    row_shift: f64,
+}
+
+#[allow(dead_code)]
+impl Viewport {
+    pub const MIN_CELL_SIZE: f64 = 0.25;
+    pub const MAX_CELL_SIZE: f64 = 64.0;
+
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size: cell_size.clamp(Self::MIN_CELL_SIZE, Self::MAX_CELL_SIZE),
+            origin: [0.0, 0.0],
+            row_shift: 0.0,
+        }
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    pub fn is_hexagonal(&self) -> bool {
+        self.row_shift != 0.0
+    }
+
+    /// Shifts each row half a cell to the left of the previous one, so that the 6 cells of the
+    /// hexagonal neighborhood (all but the top right and bottom left ones) surround each cell.
+    pub fn set_hexagonal(&mut self, hexagonal: bool) {
+        self.row_shift = if hexagonal { -0.5 } else { 0.0 };
+    }
+
+    /// Returns the screen position `[x, y]` of the top left corner of a cell.
+    pub fn cell_to_screen(&self, row: i64, column: i64) -> [f64; 2] {
+        [
+            self.origin[0] + (column as f64 + row as f64 * self.row_shift) * self.cell_size,
+            self.origin[1] + row as f64 * self.cell_size,
+        ]
+    }
+
+    /// Returns the `(row, column)` of the cell under a screen position, which may be outside the pool.
+    pub fn screen_to_cell(&self, position: [f64; 2]) -> (i64, i64) {
+        let row = ((position[1] - self.origin[1]) / self.cell_size).floor() as i64;
+        let column = ((position[0] - self.origin[0]) / self.cell_size - row as f64 * self.row_shift)
+            .floor() as i64;
+        (row, column)
+    }
+
+    /// Returns the `(row, column)` of the cell under a screen position in a pool of the given size,
+    /// `None` if the position is outside of the pool.
+    pub fn cell_at(
+        &self,
+        position: [f64; 2],
+        pool_height: u32,
+        pool_width: u32,
+    ) -> Option<(u32, u32)> {
+        let (row, column) = self.screen_to_cell(position);
+        ((0..pool_height as i64).contains(&row) && (0..pool_width as i64).contains(&column))
+            .then_some((row as u32, column as u32))
+    }
+
+    /// Returns the corners of the hexagon drawn for a cell, clockwise from the top one.
+    /// Hexagons overlap the rows above and below by a sixth of a cell, so that they tile.
+    pub fn hexagon(&self, row: i64, column: i64) -> [[f64; 2]; 6] {
+        let [x, y] = self.cell_to_screen(row, column);
+        let size = self.cell_size;
+        [
+            [x + size / 2.0, y - size / 6.0],
+            [x + size, y + size / 6.0],
+            [x + size, y + size * 5.0 / 6.0],
+            [x + size / 2.0, y + size * 7.0 / 6.0],
+            [x, y + size * 5.0 / 6.0],
+            [x, y + size / 6.0],
+        ]
+    }
+
+    /// Multiplies the cell size by the given factor, keeping the point under `position` still.
+    pub fn zoom_at(&mut self, factor: f64, position: [f64; 2]) {
+        let cell_size = (self.cell_size * factor).clamp(Self::MIN_CELL_SIZE, Self::MAX_CELL_SIZE);
+        let applied_factor = cell_size / self.cell_size;
+        for (origin, position) in self.origin.iter_mut().zip(position) {
+            *origin = position - (position - *origin) * applied_factor;
+        }
+        self.cell_size = cell_size;
+    }
+
+    /// Moves the view by the given amount of pixels.
+    pub fn pan(&mut self, delta: [f64; 2]) {
+        self.origin[0] += delta[0];
+        self.origin[1] += delta[1];
+    }
+
+    /// Zooms and centers the view so that the given area fills a view of the given size.
+    pub fn fit(&mut self, area: Area, view_size: [f64; 2]) {
+        // Shifted rows make the area wider, by the shift of its last row relative to its first one.
+        let width = area.width as f64 + area.height.saturating_sub(1) as f64 * self.row_shift.abs();
+        let cell_size =
+            (view_size[0] / width.max(1.0)).min(view_size[1] / area.height.max(1) as f64);
+        self.cell_size = cell_size.clamp(Self::MIN_CELL_SIZE, Self::MAX_CELL_SIZE);
+        let center_row = area.row as f64 + area.height as f64 / 2.0;
+        let center = [
+            area.column as f64 + area.width as f64 / 2.0 + (center_row - 0.5) * self.row_shift,
+            center_row,
+        ];
+        for ((origin, view_size), center) in self.origin.iter_mut().zip(view_size).zip(center) {
+            *origin = view_size / 2.0 - center * self.cell_size;
+        }
+    }
+
+    /// Returns the rows and columns of a pool of the given size visible in a view of the given size.
+    pub fn visible_cells(
+        &self,
+        view_size: [f64; 2],
+        pool_height: u32,
+        pool_width: u32,
+    ) -> (Range<u32>, Range<u32>) {
+        let (first_row, top_first_column) = self.screen_to_cell([0.0, 0.0]);
+        let (last_row, bottom_last_column) = self.screen_to_cell(view_size);
+        // With shifted rows, the columns visible in the first and last rows differ.
+        let (_, bottom_first_column) = self.screen_to_cell([0.0, view_size[1]]);
+        let (_, top_last_column) = self.screen_to_cell([view_size[0], 0.0]);
+        let first_column = top_first_column.min(bottom_first_column);
+        let last_column = top_last_column.max(bottom_last_column);
+        let clamp_range = |first: i64, last: i64, size: u32| {
+            first.clamp(0, size as i64) as u32..(last + 1).clamp(0, size as i64) as u32
+        };
+        (
+            clamp_range(first_row, last_row, pool_height),
+            clamp_range(first_column, last_column, pool_width),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screen_to_cell() {
+        let mut viewport = Viewport::new(10.0);
+        assert_eq!(viewport.screen_to_cell([25.0, 12.0]), (1, 2));
+        assert_eq!(viewport.screen_to_cell([-1.0, 0.0]), (0, -1));
+        viewport.pan([5.0, 20.0]);
+        assert_eq!(viewport.screen_to_cell([25.0, 12.0]), (-1, 2));
+        assert_eq!(viewport.cell_to_screen(-1, 2), [25.0, 10.0]);
+        assert_eq!(viewport.cell_at([25.0, 32.0], 3, 3), Some((1, 2)));
+        assert_eq!(viewport.cell_at([25.0, 12.0], 3, 3), None);
+        assert_eq!(viewport.cell_at([35.0, 32.0], 3, 3), None);
+    }
+
+    #[test]
+    fn zoom_keeps_cursor_cell() {
+        let mut viewport = Viewport::new(10.0);
+        let cursor = [253.0, 147.0];
+        let cell = viewport.screen_to_cell(cursor);
+        viewport.zoom_at(2.0, cursor);
+        assert_eq!(viewport.cell_size(), 20.0);
+        assert_eq!(viewport.screen_to_cell(cursor), cell);
+        viewport.zoom_at(0.1, cursor);
+        assert_eq!(viewport.screen_to_cell(cursor), cell);
+        viewport.zoom_at(1e9, cursor);
+        assert_eq!(viewport.cell_size(), Viewport::MAX_CELL_SIZE);
+    }
+
+    #[test]
+    fn fit() {
+        let mut viewport = Viewport::new(10.0);
+        let area = Area {
+            row: 10,
+            column: 20,
+            height: 5,
+            width: 10,
+        };
+        viewport.fit(area, [200.0, 200.0]);
+        assert_eq!(viewport.cell_size(), 20.0);
+        assert_eq!(viewport.cell_to_screen(10, 20), [0.0, 50.0]);
+        assert_eq!(viewport.screen_to_cell([199.0, 149.0]), (14, 29));
+    }
+
+    #[test]
+    fn visible_cells() {
+        let mut viewport = Viewport::new(10.0);
+        viewport.pan([-15.0, 0.0]);
+        assert_eq!(
+            viewport.visible_cells([100.0, 50.0], 100, 100),
+            (0..6, 1..12)
+        );
+        assert_eq!(viewport.visible_cells([100.0, 50.0], 3, 5), (0..3, 1..5));
+    }
+
+    #[test]
+    fn hexagonal() {
+        let mut viewport = Viewport::new(12.0);
+        viewport.set_hexagonal(true);
+        assert!(viewport.is_hexagonal());
+        assert_eq!(viewport.cell_to_screen(2, 3), [24.0, 24.0]);
+        assert_eq!(viewport.screen_to_cell([25.0, 30.0]), (2, 3));
+        assert_eq!(viewport.screen_to_cell([23.0, 30.0]), (2, 2));
+        // The hexagons of neighbors share an edge.
+        let hexagon = viewport.hexagon(2, 3);
+        assert_eq!(viewport.hexagon(2, 4)[5], hexagon[1]);
+        assert_eq!(viewport.hexagon(3, 4)[0], hexagon[2]);
+        assert_eq!(viewport.hexagon(1, 3)[4], hexagon[0]);
+        assert_eq!(
+            viewport.visible_cells([100.0, 50.0], 100, 100),
+            (0..5, 0..11)
+        );
+
+        viewport.fit(
+            Area {
+                row: 0,
+                column: 0,
+                height: 5,
+                width: 6,
+            },
+            [80.0, 50.0],
+        );
+        assert_eq!(viewport.cell_size(), 10.0);
+        assert_eq!(viewport.cell_to_screen(0, 0), [20.0, 0.0]);
+        assert_eq!(viewport.cell_to_screen(4, 6), [60.0, 40.0]);
+    }
+}
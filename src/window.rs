@@ -0,0 +1,326 @@
+use graphics::types::{Color, Scalar};
+use graphics::Context;
+use opengl_graphics::OpenGL;
+use piston::input::UpdateEvent;
+use piston::window::{Window as _, WindowSettings};
+use piston::{
+    Button, Event, EventLoop, Key, MouseButton, MouseCursorEvent, MouseScrollEvent, PressEvent,
+    ReleaseEvent,
+};
+use piston_window::{G2d, Glyphs, PistonWindow as Window};
+
+use crate::viewport::Viewport;
+use game_of_life::pool::Area;
+
+/// Size of a cell when the window is first opened, unless the grid would not fit on screen.
+const PIXEL_PER_CELL: f64 = 10.0;
+/// Largest initial window size, in pixels. The window can be resized afterwards.
+const MAX_WINDOW_WIDTH: f64 = 1280.0;
+const MAX_WINDOW_HEIGHT: f64 = 720.0;
+
+/// What a window shows, and how it reacts to the input which `Frame` does not handle itself.
+pub trait Scene {
+    fn width(&self) -> u32;
+
+    fn height(&self) -> u32;
+
+    /// Whether the cells are drawn as hexagons when the window opens.
+    fn hexagonal(&self) -> bool {
+        false
+    }
+
+    /// Computes the next generation, called at the chosen speed unless paused.
+    fn step(&mut self, frame: &mut Frame);
+
+    /// Draws the cells, under the help overlay.
+    fn draw(&self, viewport: &Viewport, cursor: [f64; 2], c: Context, g: &mut G2d);
+
+    /// First lines of the help overlay, followed by the keys handled by `Frame`.
+    fn help_lines(&self) -> Vec<String>;
+
+    /// Area shown by `F`.
+    fn fit_area(&self) -> Area {
+        Area {
+            row: 0,
+            column: 0,
+            height: self.height(),
+            width: self.width(),
+        }
+    }
+
+    /// Handles a key before `Frame`, returns `true` if the frame must ignore it.
+    fn key_press(&mut self, _frame: &mut Frame, _key: Key) -> bool {
+        false
+    }
+
+    fn key_release(&mut self, _key: Key) {}
+
+    fn mouse_press(&mut self, _frame: &mut Frame, _button: MouseButton) {}
+
+    fn mouse_release(&mut self, _button: MouseButton) {}
+
+    /// Called after each event while a mouse button is pressed, to paint cells while dragging.
+    fn mouse_held(&mut self, _frame: &mut Frame, _button: MouseButton) {}
+}
+
+/// The window around a scene, with its speed, pause, help, zoom and pan.
+pub struct Frame {
+    window: Window,
+    /// Font of the help overlay.
+    glyphs: Glyphs,
+    cursor: [f64; 2],
+    paused: bool,
+    mouse_button_pressed: Option<MouseButton>,
+    /// Whether the pressed button pans the view instead of being handled by the scene.
+    panning: bool,
+    shift_pressed: bool,
+    percent_speed: u8,
+    render_help: bool,
+    /// Shown above the help until the next key is pressed, even when the help is hidden.
+    message: Option<String>,
+    /// Zoom and pan of the displayed grid.
+    viewport: Viewport,
+}
+
+impl Frame {
+    const MAX_FPS: u64 = 165;
+    const SPEED_STEP: u8 = 10;
+    /// Zoom factor applied for each step of the mouse wheel.
+    const ZOOM_STEP: f64 = 1.1;
+
+    /// Opens a window sized for the given scene.
+    pub fn new<S: Scene>(title: &str, scene: &S) -> Self {
+        const FONT: &str = "assets/FiraSans-Bold.ttf";
+
+        let (width, height) = (scene.width(), scene.height());
+        let cell_size = PIXEL_PER_CELL
+            .min(MAX_WINDOW_WIDTH / width.max(1) as f64)
+            .min(MAX_WINDOW_HEIGHT / height.max(1) as f64);
+        let mut viewport = Viewport::new(cell_size);
+        viewport.set_hexagonal(scene.hexagonal());
+        let mut window: Window = WindowSettings::new(
+            title,
+            [
+                (width as f64 * viewport.cell_size()).clamp(1.0, MAX_WINDOW_WIDTH),
+                (height as f64 * viewport.cell_size()).clamp(1.0, MAX_WINDOW_HEIGHT),
+            ],
+        )
+        .graphics_api(OpenGL::V3_2)
+        .exit_on_esc(true)
+        .resizable(true)
+        .build()
+        .unwrap();
+        let glyphs = window
+            .load_font(FONT)
+            .unwrap_or_else(|_| panic!("Cannot load font {}", FONT));
+
+        Self {
+            window,
+            glyphs,
+            cursor: Default::default(),
+            paused: false,
+            mouse_button_pressed: None,
+            panning: false,
+            shift_pressed: false,
+            percent_speed: 10,
+            render_help: true,
+            message: None,
+            viewport,
+        }
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn set_percent_speed(&mut self, percent_speed: u8) {
+        self.percent_speed = percent_speed.clamp(1, 100);
+    }
+
+    /// Shows a message, such as an error, until the next key is pressed.
+    pub fn show_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    pub fn cursor(&self) -> [f64; 2] {
+        self.cursor
+    }
+
+    pub fn viewport_mut(&mut self) -> &mut Viewport {
+        &mut self.viewport
+    }
+
+    /// Returns the size of the drawing area, in pixels.
+    pub fn view_size(&self) -> [f64; 2] {
+        let size = self.window.size();
+        [size.width, size.height]
+    }
+
+    /// Returns the `(row, column)` of the cell under the cursor in a grid of the given size,
+    /// `None` if the cursor is outside of it.
+    pub fn cursor_cell(&self, height: u32, width: u32) -> Option<(u32, u32)> {
+        self.viewport.cell_at(self.cursor, height, width)
+    }
+
+    /// Zooms and centers the view on the given area.
+    pub fn fit(&mut self, area: Area) {
+        let view_size = self.view_size();
+        self.viewport.fit(area, view_size);
+    }
+
+    /// Shows the scene until the window is closed.
+    pub fn run<S: Scene>(&mut self, scene: &mut S) {
+        self.window.set_max_fps(Self::MAX_FPS);
+        self.set_updates_per_second();
+        self.window.set_lazy(false);
+
+        while let Some(e) = self.window.next() {
+            // First capture mouse position.
+            if let Some(position) = e.mouse_cursor_args() {
+                self.process_cursor_move(position);
+            }
+            if let Some(scroll) = e.mouse_scroll_args() {
+                self.viewport
+                    .zoom_at(Self::ZOOM_STEP.powf(scroll[1]), self.cursor);
+            }
+            // Then process inputs.
+            if let Some(Button::Mouse(button)) = e.press_args() {
+                // Prevent press when already pressed
+                if self.mouse_button_pressed.is_none() {
+                    self.mouse_button_pressed = Some(button);
+                    // Shift + left drag pans too, for trackpads without a middle button.
+                    self.panning = button == MouseButton::Middle
+                        || (button == MouseButton::Left && self.shift_pressed);
+                    if !self.panning {
+                        scene.mouse_press(self, button);
+                    }
+                }
+            }
+            if let Some(Button::Mouse(button)) = e.release_args() {
+                // Prevent release of another button than the pressed one
+                if self
+                    .mouse_button_pressed
+                    .is_none_or(|pressed| pressed == button)
+                {
+                    self.mouse_button_pressed = None;
+                    if !self.panning {
+                        scene.mouse_release(button);
+                    }
+                    self.panning = false;
+                }
+            }
+            if let Some(button) = self.mouse_button_pressed.filter(|_| !self.panning) {
+                scene.mouse_held(self, button);
+            }
+            if let Some(Button::Keyboard(key)) = e.press_args() {
+                self.message = None;
+                if let Key::LShift | Key::RShift = key {
+                    self.shift_pressed = true;
+                }
+                if !scene.key_press(self, key) {
+                    self.process_keyboard(key, scene);
+                }
+            }
+            if let Some(Button::Keyboard(key)) = e.release_args() {
+                if let Key::LShift | Key::RShift = key {
+                    self.shift_pressed = false;
+                }
+                scene.key_release(key);
+            }
+            // Update state accordingly.
+            if e.update_args().is_some() && !self.paused {
+                scene.step(self);
+            }
+            // Finally render.
+            self.render(&e, scene);
+        }
+    }
+
+    fn set_updates_per_second(&mut self) {
+        self.window
+            .set_ups(Self::MAX_FPS * self.percent_speed as u64 / 100);
+    }
+
+    fn process_keyboard<S: Scene>(&mut self, key: Key, scene: &S) {
+        match key {
+            // Space : Pause / Resume when space is pressed
+            Key::Space => self.paused = !self.paused,
+            // H : toggle help
+            Key::H => self.render_help = !self.render_help,
+            // F : fit the area chosen by the scene to the window
+            Key::F => self.fit(scene.fit_area()),
+            // Right / Left : modify speed
+            Key::Left => {
+                // Weird logic to set 1 instead of zero.
+                if self.percent_speed == 10 {
+                    self.percent_speed = 1;
+                } else if self.percent_speed > 10 {
+                    self.percent_speed -= Self::SPEED_STEP;
+                }
+                self.set_updates_per_second();
+            }
+            Key::Right => {
+                if self.percent_speed == 1 {
+                    self.percent_speed = 10;
+                } else if self.percent_speed < 100 {
+                    self.percent_speed += Self::SPEED_STEP;
+                }
+                self.set_updates_per_second();
+            }
+            // Discard other keys
+            _ => {}
+        }
+    }
+
+    /// Moves the cursor, panning the view while dragging with the middle button or Shift + left.
+    fn process_cursor_move(&mut self, position: [f64; 2]) {
+        if self.panning {
+            self.viewport
+                .pan([position[0] - self.cursor[0], position[1] - self.cursor[1]]);
+        }
+        self.cursor = position;
+    }
+
+    fn render<S: Scene>(&mut self, event: &Event, scene: &S) {
+        use graphics::*;
+
+        const OUTSIDE_COLOR: Color = [0.6, 0.6, 0.6, 1.0];
+        const TEXT_COLOR: Color = [0.9, 0.1, 0.1, 1.0];
+        const TEXT_HORIZONTAL_OFFSET: Scalar = 10.0;
+        const TEXT_VERTICAL_OFFSET: Scalar = 20.0;
+        const TEXT_FONT_SIZE: u32 = 16;
+
+        self.window.draw_2d(event, |c, g, device| {
+            clear(OUTSIDE_COLOR, g);
+            scene.draw(&self.viewport, self.cursor, c, g);
+
+            let help_lines = self.render_help.then(|| {
+                scene.help_lines().into_iter().chain([
+                    format!("← → : Speed : {}%", self.percent_speed),
+                    "H : toggle help".to_owned(),
+                    "Space : pause".to_owned(),
+                    "Wheel : zoom, middle or Shift + left drag : pan, F : fit".to_owned(),
+                ])
+            });
+            let lines = self
+                .message
+                .clone()
+                .into_iter()
+                .chain(help_lines.into_iter().flatten());
+            let mut vertical_position = TEXT_VERTICAL_OFFSET;
+            for line in lines {
+                text::Text::new_color(TEXT_COLOR, TEXT_FONT_SIZE)
+                    .draw(
+                        &line,
+                        &mut self.glyphs,
+                        &DrawState::default(),
+                        c.transform.trans(TEXT_HORIZONTAL_OFFSET, vertical_position),
+                        g,
+                    )
+                    .unwrap();
+                vertical_position += TEXT_VERTICAL_OFFSET;
+            }
+            self.glyphs.factory.encoder.flush(device);
+        });
+    }
+}
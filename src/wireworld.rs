@@ -0,0 +1,299 @@
+use crate::grid::StateGrid;
+use crate::pool::{compute_next_state, Topology};
+use alloc::{string::String, vec, vec::Vec};
+use core::{error, fmt, str::FromStr};
+
+/// State of a WireWorld cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Cell {
+    #[default]
+    Empty,
+    /// Wire along which electrons travel.
+    Conductor,
+    /// Front of an electron.
+    Head,
+    /// Back of an electron, which prevents it from going backward.
+    Tail,
+}
+
+impl Cell {
+    /// Every state, in the order of their numbers.
+    pub const ALL: [Cell; 4] = [Cell::Empty, Cell::Conductor, Cell::Head, Cell::Tail];
+
+    /// Returns the next state of a cell given the number of electron heads among its neighbors.
+    pub fn next(self, heads: u8) -> Cell {
+        match self {
+            Cell::Empty => Cell::Empty,
+            Cell::Conductor if heads == 1 || heads == 2 => Cell::Head,
+            Cell::Conductor => Cell::Conductor,
+            Cell::Head => Cell::Tail,
+            Cell::Tail => Cell::Conductor,
+        }
+    }
+
+    /// Character of the state in the text representation of a circuit.
+    fn to_char(self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::Conductor => '#',
+            Cell::Head => 'H',
+            Cell::Tail => 't',
+        }
+    }
+}
+
+impl From<Cell> for u8 {
+    fn from(cell: Cell) -> u8 {
+        cell as u8
+    }
+}
+
+impl TryFrom<u8> for Cell {
+    type Error = InvalidState;
+
+    fn try_from(state: u8) -> Result<Self, Self::Error> {
+        Cell::ALL
+            .get(state as usize)
+            .copied()
+            .ok_or(InvalidState(state))
+    }
+}
+
+/// Error returned when a number is not the one of a WireWorld state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidState(pub u8);
+impl fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid WireWorld state {}, expected 0 to {}",
+            self.0,
+            Cell::ALL.len() - 1
+        )
+    }
+}
+impl error::Error for InvalidState {}
+
+/// A WireWorld circuit, electrons moving along conductors.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WireWorld {
+    /// State of each cell, indexed `[row][column]`.
+    state: Vec<Vec<Cell>>,
+    /// Behavior of the edges.
+    topology: Topology,
+}
+
+impl fmt::Display for WireWorld {
+    /// Draws one line per row, `.` being empty, `#` a conductor, `H` a head and `t` a tail.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.state {
+            for cell in row {
+                write!(f, "{}", cell.to_char())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for WireWorld {
+    type Err = String;
+
+    /// Parses the representation written by `Display`, shorter lines being padded with empty cells.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().collect();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut circuit = Self::new(width as u32, lines.len() as u32);
+        for (row, line) in lines.iter().enumerate() {
+            for (column, character) in line.chars().enumerate() {
+                circuit.state[row][column] = Cell::ALL
+                    .into_iter()
+                    .find(|cell| cell.to_char() == character)
+                    .ok_or_else(|| alloc::format!("invalid WireWorld cell '{}'", character))?;
+            }
+        }
+        Ok(circuit)
+    }
+}
+
+impl WireWorld {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            state: vec![vec![Cell::Empty; width as usize]; height as usize],
+            topology: Topology::default(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.state.first().map_or(0, |row| row.len() as u32)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.state.len() as u32
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> Cell {
+        self.state[row as usize][column as usize]
+    }
+
+    pub fn set_cell(&mut self, row: u32, column: u32, cell: Cell) {
+        self.state[row as usize][column as usize] = cell;
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.state.iter_mut().flatten() {
+            *cell = Cell::Empty;
+        }
+    }
+
+    /// Computes the next state of the rows from `start` included to `stop` excluded.
+    fn next_state_rows(&self, start: u32, stop: u32) -> Vec<Vec<Cell>> {
+        let (height, width) = (self.height(), self.width());
+        (start..stop)
+            .map(|row| {
+                (0..width)
+                    .map(|column| {
+                        let heads = self.topology.count_alive_neighbors(
+                            height,
+                            width,
+                            row,
+                            column,
+                            |i, j| self.state[i as usize][j as usize] == Cell::Head,
+                        );
+                        self.get_cell(row, column).next(heads)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Computes the next generation, on several threads like `Pool::step`.
+    pub fn step(&mut self) {
+        self.state = compute_next_state(self.height(), self.width(), |start, stop| {
+            self.next_state_rows(start, stop)
+        });
+    }
+}
+
+impl StateGrid for WireWorld {
+    fn width(&self) -> u32 {
+        WireWorld::width(self)
+    }
+
+    fn height(&self) -> u32 {
+        WireWorld::height(self)
+    }
+
+    fn state_count(&self) -> u8 {
+        Cell::ALL.len() as u8
+    }
+
+    fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.get_cell(row, column).into()
+    }
+
+    fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        match Cell::try_from(state) {
+            Ok(cell) => self.set_cell(row, column, cell),
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    fn step(&mut self) {
+        WireWorld::step(self)
+    }
+
+    fn clear(&mut self) {
+        WireWorld::clear(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the generations, among the first `generations`, in which the cell is an electron head.
+    fn head_generations(
+        mut circuit: WireWorld,
+        (row, column): (u32, u32),
+        generations: u64,
+    ) -> Vec<u64> {
+        (1..=generations)
+            .filter(|_| {
+                circuit.step();
+                circuit.get_cell(row, column) == Cell::Head
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let text = ".tH#\n#...\n";
+        let circuit: WireWorld = text.parse().unwrap();
+        assert_eq!((circuit.width(), circuit.height()), (4, 2));
+        assert_eq!(circuit.get_cell(0, 2), Cell::Head);
+        assert_eq!(circuit.to_string(), text);
+        assert!("..x".parse::<WireWorld>().is_err());
+    }
+
+    #[test]
+    fn states() {
+        let mut circuit = WireWorld::new(2, 1);
+        circuit.set_state(0, 1, 3);
+        assert_eq!(circuit.get_cell(0, 1), Cell::Tail);
+        assert_eq!(Cell::try_from(4), Err(InvalidState(4)));
+        assert_eq!(
+            InvalidState(4).to_string(),
+            "invalid WireWorld state 4, expected 0 to 3"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid WireWorld state 4")]
+    fn set_invalid_state() {
+        WireWorld::new(2, 1).set_state(0, 0, 4);
+    }
+
+    #[test]
+    fn diode() {
+        let forward: WireWorld = "....##....\ntH###.####\n....##....".parse().unwrap();
+        assert_eq!(head_generations(forward, (1, 9), 20), [8]);
+
+        let backward: WireWorld = "....##....\n#####.##Ht\n....##....".parse().unwrap();
+        assert_eq!(head_generations(backward.clone(), (1, 6), 20), [2]);
+        assert_eq!(head_generations(backward, (1, 0), 20), []);
+    }
+
+    #[test]
+    fn clock() {
+        // An electron loops around a ring of 6 cells, emitting one on the wire at each turn.
+        let clock: WireWorld = ".tH.......\n#..#######\n.##.......".parse().unwrap();
+        assert_eq!(
+            head_generations(clock.clone(), (1, 9), 40),
+            [7, 13, 19, 25, 31, 37]
+        );
+
+        let mut large = WireWorld::new(256, 256);
+        for (row, line) in clock.state.iter().enumerate() {
+            for (column, cell) in line.iter().enumerate() {
+                large.set_cell(row as u32 + 100, column as u32, *cell);
+            }
+        }
+        assert_eq!(
+            head_generations(large, (101, 9), 40),
+            [7, 13, 19, 25, 31, 37]
+        );
+    }
+}